@@ -1,31 +1,50 @@
 advent_of_code::solution!(1);
 
+use advent_of_code::{
+    tools::parse::{lines, Span},
+    ParseResult, Report,
+};
+
 const LUT: [&str; 9] = [
     "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(input.lines().map(parse_line_1).sum())
+    lines(input)
+        .map(parse_line_1)
+        .sum::<ParseResult<_>>()
+        .report()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(input.lines().map(parse_line_2).sum())
+    lines(input)
+        .map(parse_line_2)
+        .sum::<ParseResult<_>>()
+        .report()
+}
+
+fn parse_line_1(line: Span) -> ParseResult<u32> {
+    let first = line.as_str().chars().find_map(|c| c.to_digit(10));
+    let last = line.as_str().chars().rev().find_map(|c| c.to_digit(10));
+    calibration_value(line, first, last)
 }
 
-fn parse_line_1(line: &str) -> u32 {
-    let first = line.chars().find_map(|c| c.to_digit(10));
-    let last = line.chars().rev().find_map(|c| c.to_digit(10));
-    10 * first.unwrap() + last.unwrap()
+fn parse_line_2(line: Span) -> ParseResult<u32> {
+    let str = line.as_str();
+    let first = find_pattern(0..str.len(), str);
+    let last = find_pattern((0..str.len()).rev(), str);
+    calibration_value(line, first, last)
 }
 
-fn parse_line_2(line: &str) -> u32 {
-    let first = find_pattern(0..line.len(), line);
-    let last = find_pattern((0..line.len()).rev(), line);
-    10 * first + last
+fn calibration_value(line: Span, first: Option<u32>, last: Option<u32>) -> ParseResult<u32> {
+    first
+        .zip(last)
+        .map(|(first, last)| 10 * first + last)
+        .ok_or_else(|| line.expected("a digit"))
 }
 
-fn find_pattern(mut it: impl Iterator<Item = usize>, line: &str) -> u32 {
-    it.find_map(|i| compare_slice(&line[i..])).unwrap()
+fn find_pattern(mut it: impl Iterator<Item = usize>, line: &str) -> Option<u32> {
+    it.find_map(|i| compare_slice(&line[i..]))
 }
 
 fn compare_slice(slice: &str) -> Option<u32> {
@@ -33,7 +52,7 @@ fn compare_slice(slice: &str) -> Option<u32> {
         .enumerate()
         .find(|(_, pattern)| slice.starts_with(*pattern))
         .map(|(i, _)| i as u32 + 1)
-        .or_else(|| slice.chars().next().unwrap().to_digit(10))
+        .or_else(|| slice.chars().next()?.to_digit(10))
 }
 
 #[cfg(test)]
@@ -1,5 +1,10 @@
 advent_of_code::solution!(2);
 
+use advent_of_code::{
+    tools::parse::{lines, Span},
+    ParseErrorKind, ParseResult, Report,
+};
+
 /* == Constants ==  */
 
 const MAX_RED: u32 = 12;
@@ -17,74 +22,75 @@ struct Set {
 
 struct Game<'a> {
     id: u32,
-    set_str: &'a str, // Reference avoids heap allocating a Vec<Set>
+    set_str: Span<'a>, // Reference avoids heap allocating a Vec<Set>
 }
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let result = input
-        .lines()
-        .map(Game::parse_str)
-        .filter(Game::is_valid)
-        .map(|game| game.id)
-        .sum();
-
-    Some(result)
+    lines(input)
+        .map(|line| {
+            let game = Game::parse_str(line)?;
+            Ok(if game.is_valid()? { game.id } else { 0 })
+        })
+        .sum::<ParseResult<_>>()
+        .report()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let result = input
-        .lines()
-        .map(Game::parse_str)
-        .map(|game| game.required_cubes())
-        .map(|set| set.cube_power())
-        .sum();
-
-    Some(result)
+    lines(input)
+        .map(|line| Ok(Game::parse_str(line)?.required_cubes()?.cube_power()))
+        .sum::<ParseResult<_>>()
+        .report()
 }
 
 impl Game<'_> {
-    fn parse_str(line: &str) -> Game {
-        let (id_str, set_str) = line.split_once(':').unwrap();
-        let id = id_str[5..].parse().unwrap();
-        Game { id, set_str }
+    fn parse_str(line: Span) -> ParseResult<Game> {
+        let [id_str, set_str] = line.scan("Game {}:{}")?;
+        let id = id_str.parse()?;
+        Ok(Game { id, set_str })
     }
 
-    fn iter_sets(&self) -> impl Iterator<Item = Set> + '_ {
-        self.set_str.split(';').map(Set::parse_str)
+    fn iter_sets(&self) -> impl Iterator<Item = ParseResult<Set>> + '_ {
+        self.set_str.split(";").map(Set::parse_str)
     }
 
-    fn is_valid(&self) -> bool {
-        self.iter_sets().all(|set| set.is_valid())
+    /// Checks every set, even after an invalid one, so that malformed sets
+    /// are still reported.
+    fn is_valid(&self) -> ParseResult<bool> {
+        self.iter_sets()
+            .try_fold(true, |valid, set| Ok(set?.is_valid() && valid))
     }
 
-    fn required_cubes(&self) -> Set {
-        self.iter_sets().fold(Set::default(), |mut set, other| {
+    fn required_cubes(&self) -> ParseResult<Set> {
+        self.iter_sets().try_fold(Set::default(), |mut set, other| {
+            let other = other?;
             set.red = set.red.max(other.red);
             set.green = set.green.max(other.green);
             set.blue = set.blue.max(other.blue);
-            set
+            Ok(set)
         })
     }
 }
 
 impl Set {
-    fn parse_str(str: &str) -> Self {
-        str.split(',')
-            .map(|term| term.trim().split_once(' ').unwrap())
-            .fold(Set::default(), |mut set, (count_str, colour)| {
-                let count = count_str.parse().unwrap();
-
-                match colour {
-                    "red" => set.red = count,
-                    "green" => set.green = count,
-                    "blue" => set.blue = count,
-                    _ => panic!("Unknown colour: {}", colour),
+    fn parse_str(str: Span) -> ParseResult<Self> {
+        str.split(",").try_fold(Set::default(), |mut set, term| {
+            let (count_str, colour) = term.trim().split_once(" ")?;
+            let count = count_str.parse()?;
+
+            match colour.as_str() {
+                "red" => set.red = count,
+                "green" => set.green = count,
+                "blue" => set.blue = count,
+                _ => {
+                    let kind = ParseErrorKind::Expected("`red`, `green` or `blue`");
+                    return Err(colour.error(kind));
                 }
+            }
 
-                set
-            })
+            Ok(set)
+        })
     }
 
     fn is_valid(&self) -> bool {
@@ -114,4 +120,10 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(2286));
     }
+
+    #[test]
+    fn test_malformed_set_after_invalid_one() {
+        let result = part_one("Game 1: 20 red; 3 purple");
+        assert_eq!(result, None);
+    }
 }
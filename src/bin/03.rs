@@ -3,15 +3,17 @@
 use colored::Colorize;
 use std::fmt::Display;
 
-use advent_of_code::tools::{Coords, UCoords};
+use advent_of_code::{
+    tools::{parse, Coords, Grid, UCoords},
+    ParseResult, Report,
+};
 
 advent_of_code::solution!(3);
 
 const GEAR: u8 = b'*';
 
-struct Schematic<'a> {
-    line_width: usize,
-    lines: Vec<&'a str>,
+struct Schematic {
+    grid: Grid<u8>,
 }
 
 struct Part {
@@ -25,7 +27,7 @@ struct Gear {
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let schematic = Schematic::parse_str(input);
+    let schematic = Schematic::parse_str(input).report()?;
 
     Some(
         schematic
@@ -39,65 +41,60 @@ pub fn part_one(input: &str) -> Option<u32> {
 pub fn part_two(input: &str) -> Option<u32> {
     Some(
         Schematic::parse_str(input)
+            .report()?
             .iter_gears()
             .map(|gear| gear.ratio)
             .sum(),
     )
 }
 
-impl Schematic<'_> {
-    fn parse_str(input: &str) -> Schematic {
-        let lines: Vec<&str> = input.lines().collect();
-        let line_width = lines[0].len();
-        Schematic { line_width, lines }
+impl Schematic {
+    /// Parses the schematic, verifying that it's rectangular and that every
+    /// part number fits in a `u32`.
+    fn parse_str(input: &str) -> ParseResult<Schematic> {
+        let lines = parse::grid_lines(input)?;
+
+        for line in &lines {
+            line.as_str()
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|number| !number.is_empty())
+                .try_for_each(|number| line.subspan(number).parse::<u32>().map(|_| ()))?;
+        }
+
+        let grid = Grid::from_lines(&lines, "a character", Some)?;
+
+        Ok(Schematic { grid })
     }
 
     /// Linear scan of the schematic, yielding each found part.
     fn iter_parts(&self) -> impl Iterator<Item = Part> + '_ {
-        self.lines
-            .iter()
+        self.grid
+            .rows()
             .enumerate()
-            .flat_map(|(y, line)| LinePartIterator::new(line, y))
+            .flat_map(|(y, row)| LinePartIterator::new(row, y))
     }
 
     /// Checks whether the part has any adjacent non-digit non-period characters.
     fn valid_part(&self, part: &Part) -> bool {
-        self.neighbourhood(part).any(|line| {
-            line.bytes()
-                .any(|char| !char.is_ascii_digit() && char != b'.')
-        })
-    }
-
-    /// Returns string slices that cover the immediate neighbourhood of a part.
-    /// Returning string slices allows for easy and safe linear iteration, without
-    /// having to worry about bounds checking.
-    fn neighbourhood(&self, part: &Part) -> impl Iterator<Item = &str> + '_ {
-        let t = part.coords.y.saturating_sub(1);
-        let b = part.coords.y.saturating_add(1).min(self.lines.len() - 1);
-        let l = part.coords.x.saturating_sub(1);
-        let r = part
-            .coords
-            .x
-            .saturating_add(part.length)
-            .min(self.line_width - 1);
-
-        self.lines[t..=b].iter().map(move |line| &line[l..=r])
+        (part.coords.x..part.coords.x + part.length)
+            .flat_map(|x| {
+                self.grid
+                    .neighbours_diagonal(UCoords::new(x, part.coords.y))
+            })
+            .any(|at| !self.grid[at].is_ascii_digit() && self.grid[at] != b'.')
     }
 
     /// Linear scan of schematic, yielding each found gear.
     fn iter_gears(&self) -> impl Iterator<Item = Gear> + '_ {
-        self.lines.iter().enumerate().flat_map(move |(y, line)| {
-            line.bytes().enumerate().filter_map(move |(x, char)| {
-                (char == GEAR)
-                    .then(|| self.read_gear(UCoords { x, y }))
-                    .flatten()
-            })
-        })
+        self.grid
+            .iter()
+            .filter(|(_, &char)| char == GEAR)
+            .filter_map(|(at, _)| self.read_gear(at))
     }
 
     /// Decodes a gear, if it is valid (has exactly two adjacent parts).
     fn read_gear(&self, at: UCoords) -> Option<Gear> {
-        if self.get(at)? != GEAR {
+        if *self.grid.get(at)? != GEAR {
             return None;
         }
 
@@ -142,58 +139,48 @@ impl Schematic<'_> {
     /// Finds a part that is located at the given coordinates.
     /// Scans left and right to find the start and end of the part.
     fn part_at(&self, at: Coords) -> Option<Part> {
-        let UCoords { x, y } = at.ucoords(&self.size())?;
-        let line = self.lines.get(y).unwrap();
-
-        if !line.as_bytes().get(x).unwrap().is_ascii_digit() {
+        if !self.grid.get(at)?.is_ascii_digit() {
             return None;
         }
 
-        let start = line[..x]
-            .rfind(|c: char| !c.is_ascii_digit())
-            .map(|idx| idx + 1)
-            .unwrap_or(0);
+        let UCoords { x, y } = at.ucoords(&self.grid.size())?;
+        let row = self.grid.row(y);
 
-        let end = line[x..]
-            .find(|c: char| !c.is_ascii_digit())
-            .map(|idx| idx + x)
-            .unwrap_or_else(|| line.len());
+        let start = row[..x]
+            .iter()
+            .rposition(|c| !c.is_ascii_digit())
+            .map_or(0, |idx| idx + 1);
 
-        let value = line[start..end].parse().unwrap();
+        let end = row[x..]
+            .iter()
+            .position(|c| !c.is_ascii_digit())
+            .map_or(row.len(), |idx| idx + x);
 
         Some(Part {
-            value,
+            value: parse_number(&row[start..end]),
             length: end - start,
             coords: UCoords { x: start, y },
         })
     }
+}
 
-    /// Returns the character at the given coordinates, if it's in bounds.
-    fn get(&self, at: UCoords) -> Option<u8> {
-        self.lines
-            .get(at.y)
-            .and_then(|line| line.as_bytes().get(at.x).copied())
-    }
-
-    /// Returns the size of the schematic.
-    fn size(&self) -> UCoords {
-        UCoords {
-            x: self.line_width,
-            y: self.lines.len(),
-        }
-    }
+/// Reads a run of digits, which was checked to fit in a `u32` while parsing.
+fn parse_number(digits: &[u8]) -> u32 {
+    digits
+        .iter()
+        .fold(0, |value, digit| value * 10 + (digit - b'0') as u32)
 }
 
 /// Iterator that does a linear scan of the schematic, line by line,
 /// yielding each found part.
 struct LinePartIterator<'a> {
     offset: usize,
-    window: Option<&'a str>,
+    window: Option<&'a [u8]>,
     y: usize,
 }
 
 impl LinePartIterator<'_> {
-    fn new(line: &str, y: usize) -> LinePartIterator {
+    fn new(line: &[u8], y: usize) -> LinePartIterator {
         LinePartIterator {
             offset: 0,
             window: if line.is_empty() { None } else { Some(line) },
@@ -208,18 +195,18 @@ impl Iterator for LinePartIterator<'_> {
     fn next(&mut self) -> Option<Self::Item> {
         let window = self.window?;
 
-        let idx = window.find(|c: char| c.is_ascii_digit())?;
+        let idx = window.iter().position(|c| c.is_ascii_digit())?;
         self.offset += idx;
 
         let (_, tail) = window.split_at(idx);
 
-        let (value, rest) = match tail.split_once(|c: char| !c.is_ascii_digit()) {
-            Some((value, rest)) => (value, Some(rest)),
+        let (value, rest) = match tail.iter().position(|c| !c.is_ascii_digit()) {
+            Some(end) => (&tail[..end], Some(&tail[end + 1..])),
             None => (tail, None),
         };
 
         let part = Part {
-            value: value.parse().unwrap(),
+            value: parse_number(value),
             length: value.len(),
             coords: UCoords {
                 x: self.offset,
@@ -236,10 +223,10 @@ impl Iterator for LinePartIterator<'_> {
 
 /* == Visualisation == */
 
-impl Display for Schematic<'_> {
+impl Display for Schematic {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (y, line) in self.lines.iter().enumerate() {
-            let mut bytes = line.bytes().enumerate();
+        for (y, row) in self.grid.rows().enumerate() {
+            let mut bytes = row.iter().copied().enumerate();
 
             while let Some((x, char)) = bytes.next() {
                 if let Some(part) = self.part_at(UCoords { x, y }.into()) {
@@ -1,23 +1,26 @@
 advent_of_code::solution!(4);
 
+use advent_of_code::{
+    tools::parse::{lines, Span},
+    ParseResult, Report,
+};
+
 const SIZE_HINT: usize = 200;
 
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| count_score(winning_count(line)))
-            .sum(),
-    )
+    lines(input)
+        .map(|line| winning_count(line).map(count_score))
+        .sum::<ParseResult<_>>()
+        .report()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let mut extra_counts: Vec<u32> = vec![0; SIZE_HINT];
     let mut count = 0;
 
-    for (i, line) in input.lines().enumerate() {
+    for (i, line) in lines(input).enumerate() {
         let instances = extra_counts.get(i).unwrap_or(&0) + 1;
-        let winners = winning_count(line);
+        let winners = winning_count(line).report()?;
 
         // Removed dynamic resizing in favour of fixed SIZE_HINT
         // let new_size = extra_counts.len().max(i + winners) + 1;
@@ -33,20 +36,14 @@ pub fn part_two(input: &str) -> Option<u32> {
     Some(count)
 }
 
-fn winning_count(line: &str) -> usize {
-    let (_, rest) = line.split_once(": ").unwrap();
-    let (target, cards) = rest.split_once(" | ").unwrap();
-
-    let targets: Vec<u8> = target
-        .split_ascii_whitespace()
-        .map(|n| n.parse().unwrap())
-        .collect();
+fn winning_count(line: Span) -> ParseResult<usize> {
+    let [_, target, cards] = line.scan("Card {}: {} | {}")?;
+    let targets: Vec<u8> = target.numbers().collect::<ParseResult<_>>()?;
 
-    cards
-        .split_ascii_whitespace()
-        .map(|n| n.parse().unwrap())
-        .filter(|n| targets.contains(n))
-        .count()
+    cards.numbers().try_fold(0, |count, n| {
+        let n = n?;
+        Ok(count + targets.contains(&n) as usize)
+    })
 }
 
 fn count_score(count: usize) -> u32 {
@@ -1,173 +1,115 @@
-use std::ops::Range;
-
-use itertools::Itertools;
+use advent_of_code::{
+    tools::{
+        interval::{Interval, IntervalMap, IntervalSet},
+        parse::{paragraphs, Paragraph, Span},
+    },
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
 
 advent_of_code::solution!(5);
 
 /* == Definitions == */
 
-struct Stage(Vec<Mapping>);
-
-struct Mapping {
-    range: Range<u64>,
-    offset: i64,
-}
-
-struct MappedRangeIterator<'a> {
-    seed: &'a Range<u64>,
-    stage: &'a Stage,
-    cursor: u64,
-}
+/// Each stage adds the offset of the mapping that contains a number.
+type Stage = IntervalMap<i64>;
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let (mut seeds, stages) = parse_input_seeds(input);
+    let (mut seeds, stages) = parse_input_seeds(input).report()?;
 
     for stage in stages {
         for seed in &mut seeds {
-            *seed = map_seed(*seed, &stage);
+            *seed = stage.map(*seed);
         }
     }
 
-    Some(seeds.into_iter().min().unwrap() as u32)
+    Some(seeds.into_iter().min()? as u32)
 }
 
+/// Maps whole ranges of seeds at once, splitting them where they straddle
+/// the edges of the mappings.
 pub fn part_two(input: &str) -> Option<u32> {
-    let (mut seeds, stages) = parse_input_seed_ranges(input);
-    let mut new_ranges = Vec::new();
+    let (seeds, stages) = parse_input_seed_ranges(input).report()?;
 
-    for stage in stages {
-        for seed_range in &mut seeds {
-            // Temporary reusable buffer required as seed_range is borrowed by the loop
-            new_ranges.extend(MappedRangeIterator::new(seed_range, &stage));
-
-            // Take one of the new ranges and overwrite the old range, this will
-            // overwrite all old seed ranges by time the loop finishes
-            *seed_range = new_ranges.pop().unwrap();
-        }
+    let locations = stages
+        .iter()
+        .fold(seeds, |seeds, stage| stage.map_set(&seeds));
 
-        seeds.extend_from_slice(&new_ranges);
-        new_ranges.clear();
-    }
-
-    Some(seeds.into_iter().map(|r| r.start).min().unwrap() as u32)
+    Some(locations.min()? as u32)
 }
 
 /* == Parsing == */
 
-fn parse_input_seeds(input: &str) -> (Vec<u64>, Vec<Stage>) {
-    let mut lines = input.lines();
-    let seed_str = &lines.next().unwrap()[7..];
-    (parse_seeds(seed_str).collect(), parse_stages(lines))
-}
-
-fn parse_input_seed_ranges(input: &str) -> (Vec<Range<u64>>, Vec<Stage>) {
-    let mut lines = input.lines();
-    let seed_str = &lines.next().unwrap()[7..];
-    let seeds = parse_seed_ranges(seed_str);
-    (seeds, parse_stages(lines))
-}
-
-fn parse_seed_ranges(seeds: &str) -> Vec<Range<u64>> {
-    parse_seeds(seeds)
-        .chunks(2)
-        .into_iter()
-        .map(|chunk| chunk.collect_tuple().map(|(a, b)| a..a + b).unwrap())
-        .collect()
+fn parse_input_seeds(input: &str) -> ParseResult<(Vec<i64>, Vec<Stage>)> {
+    let mut paragraphs = paragraphs(input);
+    let seed_str = parse_seed_line(paragraphs.next())?;
+    let seeds = seed_str.numbers().collect::<ParseResult<_>>()?;
+    Ok((seeds, parse_stages(paragraphs)?))
 }
 
-fn parse_seeds(seeds: &str) -> impl Iterator<Item = u64> + '_ {
-    seeds.split(' ').map(|s| s.parse().unwrap())
+fn parse_input_seed_ranges(input: &str) -> ParseResult<(IntervalSet<i64>, Vec<Stage>)> {
+    let mut paragraphs = paragraphs(input);
+    let seed_str = parse_seed_line(paragraphs.next())?;
+    let seeds = parse_seed_ranges(seed_str)?;
+    Ok((seeds, parse_stages(paragraphs)?))
 }
 
-fn parse_stages<'a>(lines: impl Iterator<Item = &'a str>) -> Vec<Stage> {
-    lines
-        .group_by(|s| !s.is_empty())
-        .into_iter()
-        .filter_map(|(k, v)| k.then_some(v))
-        .map(|group| Stage(group.skip(1).map(Mapping::parse_str).collect()))
-        .collect()
-}
+/// Returns the seed numbers of the first line, which must list at least one.
+fn parse_seed_line(paragraph: Option<Paragraph>) -> ParseResult<Span> {
+    let seeds = paragraph
+        .ok_or(ParseError::unexpected_end(0))?
+        .first_line()
+        .strip_prefix("seeds:")?;
 
-/* == Functions ==  */
-
-fn map_seed(seed: u64, stage: &Stage) -> u64 {
-    stage
-        .find_map(seed)
-        .map(|m| seed.wrapping_add_signed(m.offset))
-        .unwrap_or(seed)
-}
-
-/* == Implementations == */
-
-impl Mapping {
-    fn parse_str(input: &str) -> Mapping {
-        input
-            .split(' ')
-            .flat_map(|s| s.parse::<u64>())
-            .collect_tuple()
-            .map(|(t, f, s)| Mapping {
-                range: f..f + s,
-                offset: t as i64 - f as i64,
-            })
-            .unwrap()
+    match seeds.trim().is_empty() {
+        true => Err(seeds.error_at(seeds.len(), ParseErrorKind::Expected("a seed"))),
+        false => Ok(seeds.trim()),
     }
 }
 
-impl Stage {
-    fn find_map(&self, seed: u64) -> Option<&Mapping> {
-        self.0.iter().find(|mapping| mapping.range.contains(&seed))
-    }
+fn parse_seed_ranges(seeds: Span) -> ParseResult<IntervalSet<i64>> {
+    let numbers: Vec<i64> = seeds.numbers().collect::<ParseResult<_>>()?;
 
-    fn find_next_map(&self, seed: u64) -> Option<&Mapping> {
-        self.0
-            .iter()
-            .filter(|mapping| mapping.range.start > seed)
-            .min_by_key(|mapping| mapping.range.start)
+    if numbers.len() % 2 != 0 {
+        let kind = ParseErrorKind::Expected("a range length");
+        return Err(seeds.error_at(seeds.len(), kind));
     }
-}
 
-impl MappedRangeIterator<'_> {
-    fn new<'a>(seed: &'a Range<u64>, stage: &'a Stage) -> MappedRangeIterator<'a> {
-        MappedRangeIterator {
-            seed,
-            stage,
-            cursor: seed.start,
-        }
-    }
+    Ok(numbers
+        .chunks_exact(2)
+        .map(|c| Interval::new(c[0], c[0] + c[1]))
+        .collect())
 }
 
-impl Iterator for MappedRangeIterator<'_> {
-    type Item = Range<u64>;
+fn parse_stages<'a>(paragraphs: impl Iterator<Item = Paragraph<'a>>) -> ParseResult<Vec<Stage>> {
+    paragraphs
+        .map(|paragraph| {
+            let mut lines = paragraph.lines();
+            let mut stage = Stage::new();
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.cursor >= self.seed.end {
-            return None;
-        }
+            if let Some(header) = lines.next() {
+                header.strip_suffix(" map:")?;
+            }
+
+            for line in lines {
+                let (source, offset) = parse_mapping(line)?;
 
-        // Find the end of an intersecting mapping with its offset,
-        // or the start of the next one with identity mapping (no offset).
-        let (next_range, offset) = match self.stage.find_map(self.cursor) {
-            Some(mapping) => (Some(mapping.range.end), mapping.offset),
-            None => {
-                let next = self.stage.find_next_map(self.cursor).map(|m| m.range.start);
-                (next, 0)
+                if !stage.insert(source, offset) {
+                    return Err(line.expected("a range that does not overlap the others"));
+                }
             }
-        };
 
-        // Compute the next cursor, clamping it to the end of the seed range
-        let next_cursor = next_range
-            .map(|r| r.min(self.seed.end))
-            .unwrap_or(self.seed.end);
+            Ok(stage)
+        })
+        .collect()
+}
 
-        // Apply the offset to the range and return it from the iterator
-        let start = self.cursor.wrapping_add_signed(offset);
-        let end = next_cursor.wrapping_add_signed(offset);
+fn parse_mapping(input: Span) -> ParseResult<(Interval<i64>, i64)> {
+    let [t, f, s] = input.scan("{} {} {}")?;
+    let (t, f, s): (i64, i64, i64) = (t.parse()?, f.parse()?, s.parse()?);
 
-        self.cursor = next_cursor;
-        Some(start..end)
-    }
+    Ok((Interval::new(f, f + s), t - f))
 }
 
 /* == Tests == */
@@ -188,4 +130,10 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(46));
     }
+
+    #[test]
+    fn test_no_seeds() {
+        let error = parse_input_seeds("seeds: \n\nseed-to-soil map:\n50 98 2").err();
+        assert_eq!(error.map(|e| e.line()), Some(1));
+    }
 }
@@ -1,5 +1,13 @@
 advent_of_code::solution!(6);
 
+use advent_of_code::{
+    tools::{
+        math::isqrt,
+        parse::{lines, Span},
+    },
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
+
 /* == Definitions == */
 
 struct Race {
@@ -9,49 +17,66 @@ struct Race {
 
 pub fn part_one(input: &str) -> Option<u32> {
     let solution = parse_separate(input)
-        .map(|game| number_winning_possibilities(&game))
+        .report()?
+        .iter()
+        .map(number_winning_possibilities)
         .product();
 
     Some(solution)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(number_winning_possibilities(&parse_joined(input)))
+    Some(number_winning_possibilities(&parse_joined(input).report()?))
 }
 
 /* == Parsing == */
 
-fn parse_separate(input: &str) -> impl Iterator<Item = Race> + '_ {
-    let (distance_str, time_str) = parse_input(input);
+fn parse_separate(input: &str) -> ParseResult<Vec<Race>> {
+    let (distance_str, time_str) = parse_input(input)?;
+    let distances: Vec<u64> = distance_str.numbers().collect::<ParseResult<_>>()?;
+    let times: Vec<u64> = time_str.numbers().collect::<ParseResult<_>>()?;
+
+    if distances.len() != times.len() {
+        let kind = ParseErrorKind::Expected("one distance per race");
+        return Err(distance_str.error_at(distance_str.len(), kind));
+    }
+
+    let races = distances
+        .into_iter()
+        .zip(times)
+        .map(|(distance, time)| Race { time, distance });
 
-    iter_numbers(distance_str)
-        .zip(iter_numbers(time_str))
-        .map(|(distance, time)| Race { time, distance })
+    Ok(races.collect())
 }
 
-fn parse_joined(input: &str) -> Race {
-    let (distance_str, time_str) = parse_input(input);
-    let time = joined_numbers(time_str);
-    let distance = joined_numbers(distance_str);
-    Race { time, distance }
+fn parse_joined(input: &str) -> ParseResult<Race> {
+    let (distance_str, time_str) = parse_input(input)?;
+    let time = joined_numbers(time_str)?;
+    let distance = joined_numbers(distance_str)?;
+    Ok(Race { time, distance })
 }
 
-fn parse_input(input: &str) -> (&str, &str) {
-    let mut lines = input.lines().map(|l| l.split_at(9).1);
-    let time_str = lines.next().unwrap();
-    let distance_str = lines.next().unwrap();
-    (distance_str, time_str)
+fn parse_input(input: &str) -> ParseResult<(Span, Span)> {
+    let mut lines = lines(input);
+    let time_str = parse_line(lines.next(), "Time:", 0)?;
+    let distance_str = parse_line(lines.next(), "Distance:", 1)?;
+    Ok((distance_str, time_str))
 }
 
-fn iter_numbers(input: &str) -> impl Iterator<Item = u64> + '_ {
-    input.split_whitespace().map(|s| s.parse().unwrap())
+fn parse_line<'a>(line: Option<Span<'a>>, label: &'static str, n: usize) -> ParseResult<Span<'a>> {
+    line.ok_or(ParseError::unexpected_end(n))?
+        .strip_prefix(label)
 }
 
-fn joined_numbers(input: &str) -> u64 {
-    input
-        .split_whitespace()
-        .map(|s| (s.len() as u32, s.parse::<u64>().unwrap()))
-        .fold(0, |acc, (s, n)| 10_u64.pow(s) * acc + n)
+fn joined_numbers(input: Span) -> ParseResult<u64> {
+    input.split_whitespace().try_fold(0_u64, |acc, s| {
+        let n: u64 = s.parse()?;
+
+        10_u64
+            .checked_pow(s.len() as u32)
+            .and_then(|shift| shift.checked_mul(acc)?.checked_add(n))
+            .ok_or_else(|| s.error(ParseErrorKind::Invalid("u64")))
+    })
 }
 
 /* == Functions == */
@@ -66,30 +91,29 @@ fn joined_numbers(input: &str) -> u64 {
 /// of the race and d is the record distance. Grug was happy with this answer,
 /// and he wrote a program to solve it. Computer didn't get bored, unlike Grug.
 /// Grug was able to win the race.
+///
+/// The winning times are symmetric around t / 2, so it is enough to find the
+/// first one. The root of the equation is estimated with an integer square
+/// root, which can't be off by more than one, so no rounding is involved.
 fn number_winning_possibilities(race: &Race) -> u32 {
-    let a = -1.0;
-    let b = race.time as f64;
-    let c = -(race.distance as f64);
+    let (time, distance) = (race.time as u128, race.distance as u128);
+    let wins = |x: u128| x * (time - x) > distance;
 
-    let (x1, x2) = solutions_of_quadratic_equation(a, b, c);
+    let Some(discriminant) = (time * time).checked_sub(4 * distance) else {
+        return 0;
+    };
 
-    let mut diff = x2.floor() - x1.ceil() - 1.0;
-    diff += if x1.trunc() != x1 { 1.0 } else { 0.0 };
-    diff += if x2.trunc() != x2 { 1.0 } else { 0.0 };
+    // Never past the first winning time, as the square root is rounded down
+    let mut first = (time - isqrt(discriminant)) / 2;
 
-    diff as u32
-}
+    while first <= time / 2 && !wins(first) {
+        first += 1;
+    }
 
-/// Computes the solutions of the quadratic equation ax^2 + bx + c = 0.
-/// The computation is done using f64, as f32 was not sufficient for the input.
-fn solutions_of_quadratic_equation(a: f64, b: f64, c: f64) -> (f64, f64) {
-    let discriminant = b * b - 4.0 * a * c;
-    debug_assert!(discriminant > 0.0);
-    let sqrt_discriminant = discriminant.sqrt();
-    let two_a = 2.0 * a;
-    let x1 = (-b + sqrt_discriminant) / two_a;
-    let x2 = (-b - sqrt_discriminant) / two_a;
-    (x1, x2)
+    match first <= time / 2 {
+        true => (time + 1 - 2 * first) as u32,
+        false => 0,
+    }
 }
 
 /* == Tests == */
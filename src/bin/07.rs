@@ -2,6 +2,10 @@ advent_of_code::solution!(7);
 
 use std::iter;
 
+use advent_of_code::{
+    tools::parse::{lines, Span},
+    ParseResult, Report,
+};
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 
@@ -20,31 +24,46 @@ struct Hand<'a> {
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(solve(input, GameType::Normal))
+    solve(input, GameType::Normal).report()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(solve(input, GameType::Joker))
+    solve(input, GameType::Joker).report()
 }
 
 /* == Functions == */
 
-fn solve(input: &str, game_type: GameType) -> u32 {
-    input
-        .lines()
-        .flat_map(Hand::parse_str)
+fn solve(input: &str, game_type: GameType) -> ParseResult<u32> {
+    let hands: Vec<Hand> = lines(input)
+        .map(Hand::parse_str)
+        .collect::<ParseResult<_>>()?;
+
+    let winnings = hands
+        .into_iter()
         .map(|x| (x.score(game_type), x)) // pre-compute score for efficient sorting
         .sorted_unstable_by_key(|(score, _)| *score)
         .map(|(_, hand)| hand.bid)
         .enumerate()
-        .fold(0, |acc, (i, bid)| acc + (i as u32 + 1) * bid) // compute winnings
+        .fold(0, |acc, (i, bid)| acc + (i as u32 + 1) * bid); // compute winnings
+
+    Ok(winnings)
 }
 
 impl Hand<'_> {
-    fn parse_str(input: &str) -> Option<Hand> {
-        input.split_once(' ').map(|(cards, bid)| Hand {
-            bid: bid.parse().unwrap(),
-            cards,
+    /// Parses a hand, verifying that it's made of exactly five valid cards
+    /// (the scoring functions assume that they are).
+    fn parse_str(input: Span) -> ParseResult<Hand> {
+        let (cards, bid) = input.split_once(" ")?;
+
+        if cards.len() != HAND_SIZE {
+            return Err(cards.expected("five cards"));
+        }
+
+        cards.check_bytes(is_card, "a card")?;
+
+        Ok(Hand {
+            bid: bid.parse()?,
+            cards: cards.as_str(),
         })
     }
 
@@ -138,6 +157,10 @@ fn most_common_card_counts(card_scores: impl Iterator<Item = u8>) -> (u8, u8) {
     (a + joker_count, b)
 }
 
+fn is_card(card: u8) -> bool {
+    matches!(card, b'2'..=b'9' | b'T' | b'J' | b'Q' | b'K' | b'A')
+}
+
 /// The card score determines how valuable a card is, ranging from 0 to 13 for
 /// the card order (J, 2, 3, ..., 9, T, J, Q, K, A). Depending on the game type,
 /// the joker is either assigned a value of 0 (transformed) or 10 (standard).
@@ -214,12 +237,14 @@ mod tests {
     }
 
     fn tcc(hand_str: &str) -> (u8, u8) {
-        let hand = Hand::parse_str(hand_str).unwrap();
+        let hand = Hand::parse_str(Span::new(1, hand_str)).unwrap();
         super::most_common_card_counts(hand.card_numbers(GameType::Normal))
     }
 
     fn sc(hand: &str) -> u32 {
-        Hand::parse_str(hand).unwrap().score(GameType::Normal)
+        Hand::parse_str(Span::new(1, hand))
+            .unwrap()
+            .score(GameType::Normal)
     }
 
     fn js(hand: impl IntoIterator<Item = u8>) -> u32 {
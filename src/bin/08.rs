@@ -1,11 +1,19 @@
 advent_of_code::solution!(8);
 
-use std::{collections::HashMap, mem};
+use std::collections::HashMap;
+
+use advent_of_code::{
+    tools::{
+        math::Congruence,
+        parse::{lines, Span},
+    },
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
 
 /* == Definitions == */
 
-const START: &str = "AAA";
-const END: &str = "ZZZ";
+const START: NodeId = NodeId::new(*b"AAA");
+const END: NodeId = NodeId::new(*b"ZZZ");
 
 #[derive(Copy, Clone)]
 enum Cmd {
@@ -16,6 +24,8 @@ enum Cmd {
 struct Map<'a> {
     directions: &'a str,
     nodes: HashMap<NodeId, Directions>,
+    /// The number of lines of the input, to report a missing node after them.
+    lines: usize,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -26,82 +36,190 @@ struct Directions {
     right: NodeId,
 }
 
+/// The times at which a ghost stands on an end node. The path of a ghost
+/// always ends up looping, as there is a finite number of (node, command)
+/// states. Ends before the loop only happen once, those within the loop
+/// repeat with its period.
+struct Ghost {
+    loop_start: i64,
+    prefix_ends: Vec<i64>,
+    loop_ends: Vec<Congruence>,
+}
+
 /* == Solutions == */
 
+/// Follows the commands from the start node. The path loops after visiting
+/// every (node, command) state, so the end is unreachable past that point.
 pub fn part_one(input: &str) -> Option<u32> {
-    let map = Map::parse_str(input);
+    let map = Map::parse_str(input).report()?;
+    map.check_node(START).report()?;
 
-    let mut cmd_cycle = map.commands().cycle();
-    let destination = NodeId::parse_str(END);
-    let mut steps = 0;
+    let states = map.nodes.len() * map.directions.len();
+    let mut current_node = START;
 
-    let mut current_node = NodeId::parse_str(START);
+    for (steps, command) in map.commands().cycle().take(states).enumerate() {
+        if current_node == END {
+            return Some(steps as u32);
+        }
 
-    while current_node != destination {
-        let command = cmd_cycle.next().unwrap();
-        current_node = map.nodes.get(&current_node).unwrap().apply(command);
-        steps += 1;
+        current_node = map.nodes[&current_node].apply(command);
     }
 
-    Some(steps)
+    None
 }
 
+/// Finds the first time at which all ghosts stand on an end node. This is
+/// either an end before some ghost starts looping, which can be checked
+/// directly, or a common solution of the congruences of the looping ends,
+/// given by the Chinese remainder theorem. This does not assume that the
+/// loops start at 0 or that they contain a single end.
 pub fn part_two(input: &str) -> Option<u64> {
-    let map = Map::parse_str(input);
-
-    // This part makes a lot of assumptions on the problem input.
-    // Each path is cyclic and has a single start and single end node
-    // at equal distances from each other, at multiples of the number
-    // of commands. This is very fast to compute (and feasible, given the
-    // problem parameters), but is not a general solution. It works... okay?
-    map.nodes
+    let map = Map::parse_str(input).report()?;
+
+    let ghosts: Vec<_> = map
+        .nodes
         .keys()
         .filter(|node| node.is_start())
-        .map(|&node| map.steps_to_path_end(node))
-        .reduce(lcm)
+        .map(|&node| map.ghost(node))
+        .collect();
+
+    let offset = ghosts.iter().map(|g| g.loop_start).max()?;
+
+    let early = ghosts
+        .iter()
+        .flat_map(|g| &g.prefix_ends)
+        .filter(|&&time| ghosts.iter().all(|g| g.is_at_end(time)))
+        .min()
+        .copied();
+
+    // Combines the loop ends one ghost at a time, which only branches for
+    // ghosts with several ends in their loop
+    let looping = ghosts
+        .iter()
+        .try_fold(vec![Congruence::new(0, 1)], |candidates, g| {
+            let combined: Vec<_> = candidates
+                .iter()
+                .flat_map(|c| g.loop_ends.iter().filter_map(|&end| c.combine(end)))
+                .collect();
+
+            (!combined.is_empty()).then_some(combined)
+        })
+        .and_then(|candidates| {
+            candidates
+                .iter()
+                .map(|congruence| congruence.first_at_least(offset))
+                .min()
+        });
+
+    early
+        .into_iter()
+        .chain(looping)
+        .min()
+        .map(|time| time as u64)
 }
 
 /* == Implementations == */
 
 impl Map<'_> {
-    fn parse_str(input: &str) -> Map {
-        let mut it = input.lines();
-        let directions = it.next().unwrap();
-        let nodes = Self::parse_lines(it);
-        Map { directions, nodes }
+    /// Parses the directions, which must not be empty, and the nodes, whose
+    /// left and right nodes must all be defined.
+    fn parse_str(input: &str) -> ParseResult<Map> {
+        let mut it = lines(input);
+        let directions = it.next().ok_or(ParseError::unexpected_end(0))?;
+
+        if directions.is_empty() {
+            return Err(directions.expected("`L` or `R`"));
+        }
+
+        directions.check_bytes(Cmd::is_valid, "`L` or `R`")?;
+
+        let mut targets = Vec::new();
+        let nodes = Self::parse_lines(it, &mut targets)?;
+
+        if let Some((_, name)) = targets.iter().find(|(id, _)| !nodes.contains_key(id)) {
+            return Err(name.expected("a defined node"));
+        }
+
+        Ok(Map {
+            directions: directions.as_str(),
+            nodes,
+            lines: lines(input).count(),
+        })
     }
 
-    fn parse_lines<'a>(lines: impl Iterator<Item = &'a str>) -> HashMap<NodeId, Directions> {
+    /// Parses the node lines, also collecting the left and right nodes with
+    /// their spans.
+    fn parse_lines<'a>(
+        lines: impl Iterator<Item = Span<'a>>,
+        targets: &mut Vec<(NodeId, Span<'a>)>,
+    ) -> ParseResult<HashMap<NodeId, Directions>> {
         lines
-            .flat_map(|line| {
+            .filter(|line| !line.is_empty())
+            .map(|line| {
                 let (id_str, rest) = line.split_once(" = ")?;
-                let (a, b) = rest[1..rest.len() - 1].split_once(", ")?;
-
-                Some((
-                    NodeId::parse_str(id_str),
-                    Directions {
-                        left: NodeId::parse_str(a),
-                        right: NodeId::parse_str(b),
-                    },
-                ))
+                let rest = rest.strip_prefix("(")?.strip_suffix(")")?;
+                let (a, b) = rest.split_once(", ")?;
+                let (left, right) = (NodeId::parse_str(a)?, NodeId::parse_str(b)?);
+
+                targets.extend([(left, a), (right, b)]);
+                Ok((NodeId::parse_str(id_str)?, Directions { left, right }))
             })
             .collect()
     }
 
+    /// Checks that a node is defined, reporting it as missing after the input
+    /// otherwise.
+    fn check_node(&self, node: NodeId) -> ParseResult<()> {
+        match self.nodes.contains_key(&node) {
+            true => Ok(()),
+            false => Err(ParseError::new(
+                self.lines + 1,
+                1,
+                ParseErrorKind::Expected("the `AAA` node"),
+            )),
+        }
+    }
+
     fn commands(&self) -> impl ExactSizeIterator<Item = Cmd> + Clone + '_ {
         self.directions.bytes().map(Cmd::parse)
     }
 
-    fn steps_to_path_end(&self, node: NodeId) -> u64 {
-        let commands = self.commands().cycle().enumerate();
-        let mut current_node = node;
-
-        for (i, command) in commands {
-            current_node = self.nodes.get(&current_node).unwrap().apply(command);
+    /// Follows the path of a ghost until it loops, recording when it stands
+    /// on an end node. Only the states at the start of the commands are
+    /// remembered, which finds the loop at most one pass late, with the same
+    /// period.
+    fn ghost(&self, start: NodeId) -> Ghost {
+        let commands: Vec<_> = self.commands().collect();
+        let mut seen = HashMap::new();
+        let mut ends = Vec::new();
+        let mut node = start;
+
+        for time in 0.. {
+            let index = time as usize % commands.len();
+
+            if index == 0 {
+                if let Some(&loop_start) = seen.get(&node) {
+                    let period = time - loop_start;
+                    let (loop_ends, prefix_ends) = ends.into_iter().partition(|&t| t >= loop_start);
+
+                    return Ghost {
+                        loop_start,
+                        prefix_ends,
+                        loop_ends: loop_ends
+                            .into_iter()
+                            .map(|t| Congruence::new(t, period))
+                            .collect(),
+                    };
+                }
+
+                seen.insert(node, time);
+            }
 
-            if current_node.is_end() {
-                return i as u64 + 1;
+            if node.is_end() {
+                ends.push(time);
             }
+
+            node = self.nodes[&node].apply(commands[index]);
         }
 
         unreachable!();
@@ -109,9 +227,15 @@ impl Map<'_> {
 }
 
 impl NodeId {
-    fn parse_str(id: &str) -> NodeId {
-        let bytes: [u8; 3] = id.as_bytes().try_into().unwrap();
-        NodeId(bytes.iter().fold(0, |acc, byte| (acc << 8) + *byte as u32))
+    const fn new(bytes: [u8; 3]) -> NodeId {
+        NodeId((bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32)
+    }
+
+    fn parse_str(id: Span) -> ParseResult<NodeId> {
+        match id.as_bytes().try_into() {
+            Ok(bytes) => Ok(NodeId::new(bytes)),
+            Err(_) => Err(id.expected("a three-letter node")),
+        }
     }
 
     fn is_start(&self) -> bool {
@@ -123,6 +247,15 @@ impl NodeId {
     }
 }
 
+impl Ghost {
+    fn is_at_end(&self, time: i64) -> bool {
+        match time < self.loop_start {
+            true => self.prefix_ends.contains(&time),
+            false => self.loop_ends.iter().any(|c| c.contains(time)),
+        }
+    }
+}
+
 impl Directions {
     fn apply(&self, cmd: Cmd) -> NodeId {
         match cmd {
@@ -133,40 +266,20 @@ impl Directions {
 }
 
 impl Cmd {
+    fn is_valid(cmd: u8) -> bool {
+        cmd == b'L' || cmd == b'R'
+    }
+
+    /// Decodes a command, which must have been validated during parsing.
     fn parse(cmd: u8) -> Cmd {
         match cmd {
             b'L' => Cmd::Left,
             b'R' => Cmd::Right,
-            _ => panic!(),
+            _ => unreachable!(),
         }
     }
 }
 
-/* == Functions == */
-
-fn lcm(first: u64, second: u64) -> u64 {
-    first * second / gcd(first, second)
-}
-
-fn gcd(first: u64, second: u64) -> u64 {
-    let mut max = first;
-    let mut min = second;
-
-    if min > max {
-        mem::swap(&mut min, &mut max);
-    }
-
-    loop {
-        let res = max % min;
-        if res == 0 {
-            return min;
-        }
-
-        max = min;
-        min = res;
-    }
-}
-
 /* == Tests == */
 
 #[cfg(test)]
@@ -185,4 +298,34 @@ mod tests {
         let result = part_two(&read_example_part(DAY, 2));
         assert_eq!(result, Some(6));
     }
+
+    #[test]
+    fn test_part_two_offset_loops() {
+        let result = part_two(&read_example_part(DAY, 3));
+        assert_eq!(result, Some(7));
+    }
+
+    #[test]
+    fn test_part_two_several_loop_ends() {
+        let ghost_one = "11A = (11Z, 11Z)\n11Z = (12Z, 12Z)\n12Z = (11A, 11A)";
+        let ghost_two = "22A = (22B, 22B)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n\
+                         22Z = (22D, 22D)\n22D = (22A, 22A)";
+
+        let result = part_two(&format!("L\n\n{ghost_one}\n{ghost_two}"));
+        assert_eq!(result, Some(8));
+    }
+
+    #[test]
+    fn test_malformed_maps() {
+        let error = |input| Map::parse_str(input).err().map(|e| (e.line(), e.column()));
+
+        assert_eq!(error("\n\nAAA = (AAA, AAA)"), Some((1, 1)));
+        assert_eq!(error("L\n\nAAA = (BBB, AAA)"), Some((3, 8)));
+
+        let map = Map::parse_str("L\n\nBBB = (BBB, BBB)").unwrap();
+        assert_eq!(map.check_node(START).map_err(|e| e.line()), Err(4));
+
+        // ZZZ can't be reached
+        assert_eq!(part_one("L\n\nAAA = (AAA, ZZZ)\nZZZ = (ZZZ, ZZZ)"), None);
+    }
 }
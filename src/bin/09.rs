@@ -1,5 +1,10 @@
 advent_of_code::solution!(9);
 
+use advent_of_code::{
+    tools::{math::extrapolate, parse::lines},
+    ParseResult, Report,
+};
+
 /* == Definitions == */
 
 enum Order {
@@ -19,9 +24,13 @@ pub fn part_two(input: &str) -> Option<u32> {
 
 /* == Input parsing == */
 
-fn parse_input(input: &str, order: Order) -> impl Iterator<Item = Vec<i32>> + '_ {
-    input.lines().map(move |line| {
-        let parsed_line = line.split_whitespace().map(|s| s.parse().unwrap());
+fn parse_input(input: &str, order: Order) -> impl Iterator<Item = ParseResult<Vec<i64>>> + '_ {
+    lines(input).map(move |line| {
+        if line.is_empty() {
+            return Err(line.expected("a number"));
+        }
+
+        let parsed_line = line.numbers();
 
         match order {
             Order::Normal => parsed_line.collect(),
@@ -33,35 +42,14 @@ fn parse_input(input: &str, order: Order) -> impl Iterator<Item = Vec<i32>> + '_
 /* == Functions == */
 
 fn solve(input: &str, order: Order) -> Option<u32> {
-    let result: i32 = parse_input(input, order)
-        .map(|mut v| extrapolate(&mut v))
-        .sum();
+    let result: i64 = parse_input(input, order)
+        .map(|v| v.map(|v| extrapolate(&v, v.len() as i64)))
+        .sum::<ParseResult<Option<_>>>()
+        .report()??;
 
     Some(result as u32)
 }
 
-/// Extrapolate sequence by iteratively mutating slices of it
-/// in-place until the last slice is all zeroes. Returns the next
-/// predicted value in the sequence.
-fn extrapolate(sequence: &mut [i32]) -> i32 {
-    let end = sequence.len();
-    let mut accumulator = 0;
-
-    for i in 1..end {
-        accumulator += sequence[end - 1];
-
-        for j in (i..end).rev() {
-            sequence[j] -= sequence[j - 1];
-        }
-
-        if sequence[i..end].iter().all(|&n| n == 0) {
-            return accumulator;
-        }
-    }
-
-    unreachable!();
-}
-
 /* == Tests == */
 
 #[cfg(test)]
@@ -4,15 +4,15 @@ extern crate test;
 
 advent_of_code::solution!(10);
 
-use std::{fmt::Debug, iter};
+use std::fmt::Debug;
 
-use advent_of_code::tools::{algorithms::enclosed_area, *};
-use itertools::{unfold, Itertools};
+use advent_of_code::{
+    tools::{algorithms::interior_points, *},
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
 
 /* == Definitions == */
 
-const START: u8 = b'S';
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Connection {
     Horizontal,
@@ -22,11 +22,11 @@ enum Connection {
     SouthEast,
     SouthWest,
     Vertical,
+    Start,
 }
 
-struct Maze<'a> {
-    lines: Vec<&'a str>,
-    size: UCoords,
+struct Maze {
+    tiles: Grid<Connection>,
     start: UCoords,
 }
 
@@ -34,7 +34,7 @@ struct Maze<'a> {
 
 /// The distance to the farthest cell is half the length of the path.
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(Maze::parse_input(input).find_path().count() as u32 / 2)
+    Some(Maze::parse_input(input).report()?.find_path()?.len() as u32 / 2)
 }
 
 /// An elegant solution that makes use of the area of the polygon, formed
@@ -47,58 +47,52 @@ pub fn part_one(input: &str) -> Option<u32> {
 /// square of side length 1), minus an additional 1 to compensate for the 4 left/right
 /// angles containing an area of 0.25 that are required to close the loop. This works for
 /// any loop, as any additional left turns compensate the additional right turns,
-/// and vice versa.
+/// and vice versa. This is Pick's theorem, with the cells of the path as the
+/// boundary points.
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut length = 0;
-
-    let maze = Maze::parse_input(input);
-    let path = maze.find_path().inspect(|_| length += 1);
-    let area = enclosed_area(path) as u32;
-
-    Some(area - length / 2 + 1)
+    let maze = Maze::parse_input(input).report()?;
+    Some(interior_points(maze.find_path()?) as u32)
 }
 
 /* == Implementations == */
 
-impl Maze<'_> {
-    fn parse_input(input: &str) -> Maze {
-        let lines: Vec<_> = input.lines().collect();
-        let size = UCoords::new(lines[0].len(), lines.len());
-
-        let start = lines
-            .iter()
-            .enumerate()
-            .find_map(|(y, line)| {
-                line.bytes()
-                    .find_position(|&c| c == START)
-                    .map(|(x, _)| UCoords::new(x, y))
-            })
-            .unwrap();
+impl Maze {
+    fn parse_input(input: &str) -> ParseResult<Maze> {
+        let tiles = Grid::parse_with(input, "a pipe tile", |b| b.try_into().ok())?;
+
+        let start = tiles.position(|&c| c == Connection::Start).ok_or_else(|| {
+            let kind = ParseErrorKind::Expected("a starting tile `S`");
+            ParseError::new(1, 1, kind)
+        })?;
 
-        Maze { lines, size, start }
+        Ok(Maze { tiles, start })
     }
 
     fn get(&self, coords: UCoords) -> Connection {
-        self.lines[coords.y].as_bytes()[coords.x].into()
+        self.tiles[coords]
     }
 
-    fn find_path(&self) -> impl Iterator<Item = Coords> + '_ {
-        let last = self.start;
-        let current = self.connecting_cells(self.start).next().unwrap();
-
-        iter::once(last.into())
-            .chain(iter::once(current.into()))
-            .chain(unfold((last, current), |(last, current)| {
-                let next = self.connected_cells(*current).find(|c| c != last).unwrap();
-
-                if next == self.start {
-                    None
-                } else {
-                    *last = *current;
-                    *current = next;
-                    Some(next.into())
-                }
-            }))
+    /// Follows the loop from the start, returning the cells along it. Each
+    /// pipe must connect back to the previous one, so a broken loop ends
+    /// the path with [`None`] rather than wandering off it.
+    fn find_path(&self) -> Option<Vec<Coords>> {
+        let (mut last, mut current) = (self.start, self.connecting_cells(self.start).next()?);
+        let mut path = vec![last.into()];
+
+        while current != self.start {
+            path.push(current.into());
+
+            let next = self.connected_cells(current).find(|&c| c != last)?;
+            let connected = next == self.start || self.connected_cells(next).any(|c| c == current);
+
+            if !connected {
+                return None;
+            }
+
+            (last, current) = (current, next);
+        }
+
+        Some(path)
     }
 
     /// Returns the cells that are connected to the current cell, based on the symbol
@@ -109,10 +103,11 @@ impl Maze<'_> {
 
         // Iterate over adjacent cells that are connections, compute their connection
         // offsets and find the one that leads back to the current cell.
-        self.adjacent_cells(to)
+        self.tiles
+            .neighbours(to)
             .flat_map(|ucoords| {
                 let offsets = self.get(ucoords).offsets();
-                offsets.map(|os| os.map(|o| (ucoords, Coords::from(ucoords) + o)))
+                offsets.map(|os| os.map(|o| (ucoords, Coords::from(ucoords) + o.coords())))
             })
             .flatten()
             .filter(move |(_, connected_cell)| *connected_cell == from_coords)
@@ -124,48 +119,41 @@ impl Maze<'_> {
         self.get(at)
             .offsets()
             .into_iter()
-            .flat_map(move |offsets| offsets.map(|o| Coords::from(at) + o))
-            .filter_map(|coords| coords.ucoords(&self.size))
-    }
-
-    /// Returns all adjacent cells that are in the bounds of the maze.
-    fn adjacent_cells(&self, at: UCoords) -> impl Iterator<Item = UCoords> + '_ {
-        let coords = Coords::from(at);
-
-        (-1_i64..=1)
-            .cartesian_product(-1..=1)
-            .filter(|(i, j)| i != j)
-            .map(move |(i, j)| coords + (i, j).into())
-            .filter_map(|coords| coords.ucoords(&self.size))
+            .flatten()
+            .filter_map(move |direction| self.tiles.step(at, direction))
     }
 }
 
 impl Connection {
-    fn offsets(&self) -> Option<[Coords; 2]> {
-        let c = Coords::new;
+    fn offsets(&self) -> Option<[Direction; 2]> {
+        use Direction::*;
 
         Some(match self {
-            Connection::Horizontal => [c(-1, 0), c(1, 0)],
-            Connection::NorthEast => [c(0, -1), c(1, 0)],
-            Connection::NorthWest => [c(-1, 0), c(0, -1)],
-            Connection::SouthEast => [c(0, 1), c(1, 0)],
-            Connection::SouthWest => [c(-1, 0), c(0, 1)],
-            Connection::Vertical => [c(0, -1), c(0, 1)],
-            Connection::None => return None,
+            Connection::Horizontal => [West, East],
+            Connection::NorthEast => [North, East],
+            Connection::NorthWest => [West, North],
+            Connection::SouthEast => [South, East],
+            Connection::SouthWest => [West, South],
+            Connection::Vertical => [North, South],
+            Connection::None | Connection::Start => return None,
         })
     }
 }
 
-impl From<u8> for Connection {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for Connection {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'-' => Connection::Horizontal,
-            b'L' => Connection::NorthEast,
-            b'J' => Connection::NorthWest,
-            b'F' => Connection::SouthEast,
-            b'7' => Connection::SouthWest,
-            b'|' => Connection::Vertical,
-            _ => Connection::None,
+            b'-' => Ok(Connection::Horizontal),
+            b'L' => Ok(Connection::NorthEast),
+            b'J' => Ok(Connection::NorthWest),
+            b'F' => Ok(Connection::SouthEast),
+            b'7' => Ok(Connection::SouthWest),
+            b'|' => Ok(Connection::Vertical),
+            b'.' => Ok(Connection::None),
+            b'S' => Ok(Connection::Start),
+            _ => Err(()),
         }
     }
 }
@@ -195,6 +183,16 @@ mod tests {
         assert_eq!(result, Some(4));
     }
 
+    #[test]
+    fn test_malformed_maze() {
+        let error = Maze::parse_input("S-7\n|x|\nL-J").err().unwrap();
+        assert_eq!((error.line(), error.column()), (2, 2));
+
+        // A start without connections, and a loop broken by a `.`
+        assert_eq!(part_one("...\n.S.\n..."), None);
+        assert_eq!(part_one("S-7\n|.|\nL.J"), None);
+    }
+
     #[bench]
     fn profile_input_parsing(b: &mut test::Bencher) {
         let input = read_input(DAY);
@@ -204,14 +202,14 @@ mod tests {
     #[bench]
     fn profile_path_finding(b: &mut test::Bencher) {
         let input = read_input(DAY);
-        let maze = Maze::parse_input(&input);
-        b.iter(|| maze.find_path().next());
+        let maze = Maze::parse_input(&input).unwrap();
+        b.iter(|| maze.find_path());
     }
 
     #[bench]
     fn profile_connected_cells(b: &mut test::Bencher) {
         let input = read_input(DAY);
-        let maze = Maze::parse_input(&input);
+        let maze = Maze::parse_input(&input).unwrap();
         let cursor = UCoords::new(90, 86);
         b.iter(|| maze.connected_cells(cursor).last());
     }
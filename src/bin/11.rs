@@ -1,6 +1,9 @@
 advent_of_code::solution!(11);
 
-use advent_of_code::tools::*;
+use advent_of_code::{
+    tools::{parse::grid_lines, *},
+    ParseErrorKind, ParseResult, Report,
+};
 use itertools::Itertools;
 
 /* == Definitions == */
@@ -14,17 +17,16 @@ struct Map {
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u64> {
-    Some(solve(input, 2))
+    solve(input, 2).report()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    Some(solve(input, 1_000_000))
+    solve(input, 1_000_000).report()
 }
 
-fn solve(input: &str, expansion_coefficient: u64) -> u64 {
-    Map::from_str(input)
-        .distances_l1(expansion_coefficient)
-        .sum()
+fn solve(input: &str, expansion_coefficient: u64) -> ParseResult<u64> {
+    let map = Map::from_str(input)?;
+    Ok(map.distances_l1(expansion_coefficient).sum())
 }
 
 /* == Implementations == */
@@ -34,8 +36,9 @@ impl Map {
     /// rows and columns in a single pass. The `empty_rows` and `empty_cols` vectors
     /// have the same size as the input, containing a boolean of whether than row or
     /// column is empty.
-    fn from_str(input: &str) -> Map {
-        let row_size = input.lines().next().unwrap().len();
+    fn from_str(input: &str) -> ParseResult<Map> {
+        let grid = grid_lines(input)?;
+        let row_size = grid[0].len();
 
         // Store a vector of booleans indicating whether each column/row is empty.
         // This provides O(1) lookup using a range, rather than O(log n).
@@ -43,27 +46,35 @@ impl Map {
         let mut empty_rows = Vec::new();
         let mut galaxies = Vec::new();
 
-        for (y, line) in input.lines().enumerate() {
+        for (y, line) in grid.iter().enumerate() {
             let mut row_empty = true;
 
-            let zipped_iterator = line.bytes().enumerate().zip(empty_cols.iter_mut());
+            let zipped_iterator = line
+                .as_bytes()
+                .iter()
+                .enumerate()
+                .zip(empty_cols.iter_mut());
 
             for ((x, symbol), empty_column) in zipped_iterator {
-                if symbol == b'#' {
-                    row_empty = false;
-                    *empty_column = false;
-                    galaxies.push(UCoords::new(x, y));
+                match symbol {
+                    b'#' => {
+                        row_empty = false;
+                        *empty_column = false;
+                        galaxies.push(UCoords::new(x, y));
+                    }
+                    b'.' => (),
+                    _ => return Err(line.error_at(x, ParseErrorKind::Expected("`.` or `#`"))),
                 }
             }
 
             empty_rows.push(row_empty);
         }
 
-        Map {
+        Ok(Map {
             empty_cols,
             empty_rows,
             galaxies,
-        }
+        })
     }
 
     /// Returns an iterator of distances between all pairs of galaxies using the L1
@@ -109,12 +120,12 @@ mod tests {
     #[test]
     fn test_expansion_10() {
         let input = &read_example(DAY);
-        assert_eq!(solve(input, 10), 1030);
+        assert_eq!(solve(input, 10), Ok(1030));
     }
 
     #[test]
     fn test_expansion_100() {
         let input = &read_example(DAY);
-        assert_eq!(solve(input, 100), 8410);
+        assert_eq!(solve(input, 100), Ok(8410));
     }
 }
@@ -1,89 +1,75 @@
 advent_of_code::solution!(13);
 
-use std::mem;
-
-use advent_of_code::tools::*;
-use itertools::unfold;
+use advent_of_code::{
+    tools::{
+        parse::{paragraphs, Span},
+        *,
+    },
+    ParseResult, Report,
+};
 
 /* == Definitions == */
 
 const HORIZONTAL_MULTIPLIER: usize = 100;
 
-struct Maze<'a> {
-    lines: Vec<&'a str>,
+struct Maze {
+    grid: Grid<u8>,
 }
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(solve(input, 0))
+    solve(input, 0).report()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(solve(input, 1))
+    solve(input, 1).report()
 }
 
-fn solve(input: &str, errors: u32) -> u32 {
-    parse_input(input)
-        .map(|maze| {
-            maze.horizontal_reflection(errors)
+/// Streams the input into a sequence of mazes in a single pass, reporting
+/// mazes without a line of reflection at their first line.
+fn solve(input: &str, errors: u32) -> ParseResult<u32> {
+    paragraphs(input)
+        .map(|paragraph| {
+            let lines: Vec<_> = paragraph.lines().collect();
+            let maze = Maze::parse_lines(&lines)?;
+
+            let summary = maze
+                .horizontal_reflection(errors)
                 .map(|x| HORIZONTAL_MULTIPLIER * x)
                 .or_else(|| maze.vertical_reflection(errors))
-                .unwrap() as u32
+                .ok_or_else(|| paragraph.first_line().expected("a line of reflection"))?;
+
+            Ok(summary as u32)
         })
         .sum()
 }
 
-/* == Input parsing == */
-
-/// Stream the input into a sequence of mazes in a single pass.
-fn parse_input(input: &str) -> impl Iterator<Item = Maze> {
-    unfold((input.lines(), Vec::new(), false), |(lines, acc, ended)| {
-        if *ended {
-            return None;
-        }
-
-        match lines.next() {
-            Some(line) => {
-                if !line.is_empty() {
-                    acc.push(line);
-                    return Some(None);
-                }
-            }
-            None => {
-                *ended = true;
-            }
-        }
-
-        let mut lines = Vec::new();
-        mem::swap(&mut lines, acc);
-        Some(Some(Maze { lines }))
-    })
-    .flatten()
-}
-
 /* == Implementations == */
 
-impl Maze<'_> {
-    fn size(&self) -> UCoords {
-        UCoords {
-            x: self.lines.first().map_or(0, |f| f.len()),
-            y: self.lines.len(),
-        }
+impl Maze {
+    fn parse_lines(lines: &[Span]) -> ParseResult<Maze> {
+        let grid = Grid::from_lines(lines, "`.` or `#`", |c| {
+            matches!(c, b'.' | b'#').then_some(c)
+        })?;
+
+        Ok(Maze { grid })
     }
 
     /// Returns the index of the row that can be cut to get the given
     /// number of reflection errors.
     fn horizontal_reflection(&self, errors: u32) -> Option<usize> {
-        for cut in 1..self.size().y {
-            let size = cut.min(self.size().y - cut);
+        let height = self.grid.height();
 
-            let rows_above = self.lines[cut - size..cut].iter().rev();
-            let rows_below = self.lines[cut..cut + size].iter();
+        for cut in 1..height {
+            let size = cut.min(height - cut);
+
+            let rows_above = (cut - size..cut).rev().map(|y| self.grid.row(y));
+            let rows_below = (cut..cut + size).map(|y| self.grid.row(y));
 
             let found_errors = rows_above
                 .zip(rows_below)
-                .flat_map(|(a, b)| a.bytes().zip(b.bytes()).filter(|(a, b)| a != b))
+                .flat_map(|(a, b)| a.iter().zip(b).filter(|(a, b)| a != b))
                 .count() as u32;
 
             if found_errors == errors {
@@ -97,13 +83,16 @@ impl Maze<'_> {
     /// Returns the index of the column that can be cut to get the given
     /// number of reflection errors.
     fn vertical_reflection(&self, errors: u32) -> Option<usize> {
-        for cut in 1..self.size().x {
-            let size = cut.min(self.size().x - cut);
+        let width = self.grid.width();
+
+        for cut in 1..width {
+            let size = cut.min(width - cut);
 
             let found_errors = (0..size)
                 .flat_map(|i| {
-                    self.iter_column(cut - i - 1)
-                        .zip(self.iter_column(cut + i))
+                    self.grid
+                        .column(cut - i - 1)
+                        .zip(self.grid.column(cut + i))
                         .filter(|(a, b)| a != b)
                 })
                 .count() as u32;
@@ -115,11 +104,6 @@ impl Maze<'_> {
 
         None
     }
-
-    /// Returns an iterator over the characters of the given column.
-    fn iter_column(&self, i: usize) -> impl Iterator<Item = u8> + '_ {
-        self.lines.iter().map(move |line| line.as_bytes()[i])
-    }
 }
 
 /* == Tests == */
@@ -140,4 +124,10 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(400));
     }
+
+    #[test]
+    fn test_no_reflection() {
+        let error = solve("##\n..\n\n#..\n.#.\n..#", 0).err();
+        assert_eq!(error.map(|e| (e.line(), e.column())), Some((4, 1)));
+    }
 }
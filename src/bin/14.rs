@@ -1,273 +1,424 @@
 advent_of_code::solution!(14);
 
 use std::{
-    collections::BTreeMap,
-    hash::{DefaultHasher, Hash, Hasher},
+    borrow::Cow,
+    hash::{Hash, Hasher},
+    iter,
+    ops::Range,
 };
 
-use advent_of_code::tools::{Coords, UCoords};
+use advent_of_code::{
+    tools::{cycle, Direction, Grid, UCoords},
+    ParseResult, Report,
+};
 
 /* == Definitions == */
 
 const SPIN_CYCLES: usize = 1_000_000_000;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Occupation {
     Empty,
     Fixed,
     Rolling,
 }
 
-enum Direction {
-    East,
-    North,
-    South,
-    West,
+/// Whether the lines of a [`BitMatrix`] are the rows or the columns of the
+/// platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Lines {
+    Rows,
+    Columns,
+}
+
+/// A square matrix of bits, whose side is a multiple of 64 so that each line
+/// is a whole number of words.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BitMatrix {
+    words: usize,
+    bits: Vec<u64>,
 }
 
+/// The lines of the platform along the rows or the columns.
+struct Layout {
+    /// The runs of free cells of each line, between two cube rocks or the
+    /// edges of the platform, where the rounded rocks of a run gather when
+    /// the platform is tilted. The runs of all lines follow each other, to
+    /// avoid an allocation per line.
+    segments: Vec<Range<usize>>,
+    /// The index in `segments` after the last run of each line.
+    ends: Vec<usize>,
+}
+
+/// The cube rocks of the platform, which never move.
 struct Platform {
-    occupation: Vec<Occupation>,
     size: UCoords,
+    rows: Layout,
+    columns: Layout,
+}
+
+/// The rounded rocks, with a bit for each cell along either the rows or the
+/// columns of the platform, and a hash of the number of rocks in each segment
+/// of those lines.
+#[derive(Clone, Debug)]
+struct Rocks {
+    matrix: BitMatrix,
+    lines: Lines,
+    hash: u64,
 }
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let mut platform = Platform::parse_str(input);
-    let load = platform.tilt_platform(Direction::North);
-    Some(load)
+    let (platform, rocks) = Platform::parse_str(input).report()?;
+    let rocks = platform.tilt(&rocks, Direction::North);
+    Some(platform.north_beam_load(&rocks))
 }
 
+/// The platform ends up in a loop after a few spin cycles, so the state after
+/// the last one can be found within the first turn of the loop.
 pub fn part_two(input: &str) -> Option<u64> {
-    let mut platform = Platform::parse_str(input);
+    let (platform, rocks) = Platform::parse_str(input).report()?;
+    let spins = cycle::detect(rocks, |rocks| platform.spin(rocks));
 
-    let mut hashes = BTreeMap::new();
-    let mut load = 0;
+    Some(spins.nth_value(SPIN_CYCLES, |rocks| platform.north_beam_load(rocks)))
+}
 
-    // Keep spinning the cycle and saving the load
-    for i in 0..SPIN_CYCLES {
-        load = platform.spin_platform();
-        let hash = platform.hash();
+/* == Input parsing == */
 
-        // Thankfully, a cycle is always found before the end...
-        // match history.iter().enumerate().find(|(_, (h, _))| *h == hash) {
-        match hashes.get(&hash) {
-            None => {
-                hashes.insert(hash, (i, platform.north_beam_load()));
+impl Platform {
+    /// Parses the platform, splitting the cube rocks from the rounded ones.
+    /// The rounded rocks are set along the columns, ready to be tilted North
+    /// without transposing anything.
+    fn parse_str(input: &str) -> ParseResult<(Platform, Rocks)> {
+        let grid: Grid<Occupation> =
+            Grid::parse_with(input, "`.`, `#` or `O`", |c| c.try_into().ok())?;
+        let size = grid.size();
+        let mut matrix = BitMatrix::new(size.x.max(size.y));
+
+        for (y, row) in grid.rows().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                if cell == Occupation::Rolling {
+                    matrix.set(x, y);
+                }
             }
+        }
 
-            Some((j, _)) => {
-                // Compute the expected load after SPIN_CYCLES by exploiting
-                // the cyclic nature and the saved loads after each cycle.
-                let index = j - 1 + (SPIN_CYCLES - i) % (i - j);
+        let platform = Platform {
+            size,
+            rows: Layout::new(grid.rows().map(|row| row.iter()), size.x),
+            columns: Layout::new(grid.columns(), size.y),
+        };
 
-                load = *hashes
-                    .values()
-                    .find(|(i, _)| *i == index)
-                    .map(|(_, l)| l)
-                    .unwrap();
+        let rocks = Rocks {
+            hash: platform.columns.hash(&matrix),
+            matrix,
+            lines: Lines::Columns,
+        };
 
-                break;
-            }
-        }
+        Ok((platform, rocks))
     }
-
-    Some(load)
 }
 
 /* == Implementations == */
 
 impl Platform {
-    fn parse_str(input: &str) -> Platform {
-        let mut occupation = Vec::new();
-        let mut height = 0;
+    /// Tilts the platform North, West, South and East.
+    fn spin(&self, rocks: &Rocks) -> Rocks {
+        let north = self.tilt(rocks, Direction::North);
 
-        for line in input.lines() {
-            occupation.extend(line.bytes().map(Occupation::from));
-            height += 1;
-        }
+        [Direction::West, Direction::South, Direction::East]
+            .into_iter()
+            .fold(north, |rocks, direction| self.tilt(&rocks, direction))
+    }
 
-        let width = occupation.len() / height;
+    /// Tilts the platform in the given direction, working along the rows or
+    /// the columns depending on the direction.
+    ///
+    /// The rounded rocks of a segment all roll to the same end of it, so only
+    /// their number matters: it is counted and the same number of bits is set
+    /// at that end, a word at a time. Those numbers are then all it takes to
+    /// tell the tilted rocks apart, so they are also folded into the hash as
+    /// they are counted, instead of hashing the whole matrix afterwards.
+    fn tilt(&self, rocks: &Rocks, direction: Direction) -> Rocks {
+        let (lines, layout, towards_end) = match direction {
+            Direction::North => (Lines::Columns, &self.columns, false),
+            Direction::South => (Lines::Columns, &self.columns, true),
+            Direction::West => (Lines::Rows, &self.rows, false),
+            Direction::East => (Lines::Rows, &self.rows, true),
+        };
+
+        let matrix = rocks.along(lines);
+        let mut tilted = BitMatrix::new(matrix.side());
+        let mut hash = 0;
+
+        for (index, segments) in layout.lines().enumerate() {
+            let (line, target) = (matrix.line(index), tilted.line_mut(index));
+
+            for &Range { start, end } in segments {
+                let count = count_ones(line, start, end);
+                hash = combine(hash, count);
+
+                match towards_end {
+                    true => fill(target, end - count, end),
+                    false => fill(target, start, start + count),
+                }
+            }
+        }
 
-        Platform {
-            occupation,
-            size: UCoords::new(width, height),
+        Rocks {
+            matrix: tilted,
+            lines,
+            hash,
         }
     }
 
-    /// Tilts the platform North, West, South and East, returning the
-    /// final load north beam load.
-    fn spin_platform(&mut self) -> u64 {
-        self.tilt_platform(Direction::North);
-        self.tilt_platform(Direction::West);
-        self.tilt_platform(Direction::South);
-        self.tilt_platform(Direction::East)
-    }
+    /// Computes the load of the north beam, by multiplying the number of
+    /// rounded rocks on each row by its distance to the south end. Rocks along
+    /// the columns are read as they are, where the index of a bit is its row,
+    /// rather than transposed back.
+    fn north_beam_load(&self, rocks: &Rocks) -> u64 {
+        let height = self.size.y as u64;
 
-    /// Tilts the platform in the given direction, returning the load
-    /// of the north beam.
-    fn tilt_platform(&mut self, direction: Direction) -> u64 {
-        // Not really sure how to do this with less code, without macros
-        // or dynamic dispatching/allocation. This works fine.
-        match direction {
-            Direction::East => (0..self.size.y)
+        match rocks.lines {
+            Lines::Rows => (0..self.size.y)
                 .map(|y| {
-                    let minimum = UCoords::new(self.size.x - 1, y).into();
-                    let gradient = Coords::new(-1, 0);
-                    self.process_line(minimum, gradient)
+                    let count = count_ones(rocks.matrix.line(y), 0, self.size.x);
+                    (height - y as u64) * count as u64
                 })
                 .sum(),
 
-            Direction::North => (0..self.size.x)
-                .map(|x| {
-                    let minimum = UCoords::new(x, 0).into();
-                    let gradient = Coords::new(0, 1);
-                    self.process_line(minimum, gradient)
+            Lines::Columns => (0..self.size.x)
+                .flat_map(|x| rocks.matrix.line(x).iter().enumerate())
+                .map(|(i, &word)| {
+                    let count = word.count_ones() as u64;
+                    let rows: u64 = bits(word).map(|bit| (i * 64 + bit) as u64).sum();
+                    height * count - rows
                 })
                 .sum(),
+        }
+    }
+}
 
-            Direction::South => (0..self.size.x)
-                .map(|x| {
-                    let minimum = UCoords::new(x, self.size.y - 1).into();
-                    let gradient = Coords::new(0, -1);
-                    self.process_line(minimum, gradient)
-                })
-                .sum(),
+impl Layout {
+    /// Finds the free runs of each line, from the cells along the lines.
+    fn new<'a>(
+        lines: impl Iterator<Item = impl Iterator<Item = &'a Occupation>>,
+        length: usize,
+    ) -> Self {
+        let mut layout = Layout {
+            segments: Vec::new(),
+            ends: Vec::new(),
+        };
+
+        for line in lines {
+            let mut start = 0;
+
+            for (index, &cell) in line.enumerate() {
+                if cell == Occupation::Fixed {
+                    layout.push(start..index);
+                    start = index + 1;
+                }
+            }
 
-            Direction::West => (0..self.size.y)
-                .map(|y| {
-                    let minimum = UCoords::new(0, y).into();
-                    let gradient = Coords::new(1, 0);
-                    self.process_line(minimum, gradient)
-                })
-                .sum(),
+            layout.push(start..length);
+            layout.ends.push(layout.segments.len());
         }
+
+        layout
     }
 
-    /// Shifts all rolling stones in a single line of the platform.
-    /// The minimum should be the coordinates at the bottom of the slope,
-    /// with the gradient being a unit vector pointing up the slope.
-    ///
-    /// This algorithm works by iterating over each free spot, peeking ahead
-    /// to find the next rolling stone and swapping them. Encountering fixed
-    /// objects during the peek will jump the search to the spot after it.
-    ///
-    /// Efficiently collects and returns the final load of the line, **computed
-    /// in the direction of the gradient** (not always the north beam!).
-    fn process_line(&mut self, minimum: Coords, gradient: Coords) -> u64 {
-        let mut at = minimum;
-
-        // Determine the distance based on platform size (should be square anyway...)
-        let mut distance = match gradient {
-            Coords { x: 0, y: _ } => self.size.y,
-            Coords { x: _, y: 0 } => self.size.x,
-            _ => unreachable!(),
-        } as u64;
-
-        // Collect the load during the pass
-        let mut load = 0;
-
-        'outer: loop {
-            // Check the current position
-            match at.ucoords(&self.size).and_then(|x| Some((x, self.get(x)?))) {
-                Some((at_ucoords, Occupation::Empty)) => {
-                    // If it's empty, search for the nearest rolling stone
-
-                    let mut cursor = at + gradient;
-
-                    loop {
-                        match cursor
-                            .ucoords(&self.size)
-                            .and_then(|x| Some((x, self.get(x)?)))
-                        {
-                            // Rolling stone found, roll it to current position
-                            Some((cursor_ucoords, Occupation::Rolling)) => {
-                                self.set(cursor_ucoords, Occupation::Empty);
-                                self.set(at_ucoords, Occupation::Rolling);
-                                load += distance;
-                                break;
-                            }
-
-                            // Next object is static, move search to after it
-                            Some((_, Occupation::Fixed)) => {
-                                distance -= (cursor - at).norm_l1();
-                                at = cursor;
-                                break;
-                            }
-
-                            // Keep going...
-                            Some((_, Occupation::Empty)) => (),
-
-                            // Cursor left the map, we're done here
-                            None => break 'outer,
-                        }
-
-                        cursor += gradient;
-                    }
-                }
+    /// Adds a free run to the current line, unless it is empty.
+    fn push(&mut self, run: Range<usize>) {
+        if !run.is_empty() {
+            self.segments.push(run);
+        }
+    }
 
-                // We're on a rolling stone, add it to the load
-                Some((_, Occupation::Rolling)) => load += distance,
+    /// Iterates over the runs of each line.
+    fn lines(&self) -> impl Iterator<Item = &[Range<usize>]> + '_ {
+        let starts = iter::once(0).chain(self.ends.iter().copied());
+        starts
+            .zip(&self.ends)
+            .map(|(start, &end)| &self.segments[start..end])
+    }
 
-                // Immovable object
-                Some((_, Occupation::Fixed)) => (),
+    /// Hashes the number of rocks in each segment, like [`Platform::tilt`]
+    /// does for the rocks it tilts.
+    fn hash(&self, matrix: &BitMatrix) -> u64 {
+        self.lines()
+            .enumerate()
+            .flat_map(|(index, segments)| {
+                let line = matrix.line(index);
+                segments
+                    .iter()
+                    .map(|range| count_ones(line, range.start, range.end))
+            })
+            .fold(0, combine)
+    }
+}
 
-                // Reached the end, all done
-                None => break 'outer,
-            }
+impl Rocks {
+    /// Returns the rocks along the rows or the columns, transposing them if
+    /// they are along the other ones.
+    fn along(&self, lines: Lines) -> Cow<'_, BitMatrix> {
+        match self.lines == lines {
+            true => Cow::Borrowed(&self.matrix),
+            false => Cow::Owned(self.matrix.transpose()),
+        }
+    }
+}
 
-            distance -= 1;
-            at += gradient;
+impl BitMatrix {
+    /// Creates an empty matrix with at least the given side.
+    fn new(side: usize) -> Self {
+        let words = side.div_ceil(64);
+
+        BitMatrix {
+            words,
+            bits: vec![0; words * words * 64],
         }
+    }
 
-        load
+    fn side(&self) -> usize {
+        self.words * 64
     }
 
-    /// Computes the load of the north beam, by multiplying each rolling stone by
-    /// the distance to south end of the platform.
-    fn north_beam_load(&self) -> u64 {
-        (0..self.size.y)
-            .map(|y| {
-                let distance = self.size.y - y;
-
-                distance
-                    * self.occupation[y * self.size.x..(y + 1) * self.size.x]
-                        .iter()
-                        .filter(|&&x| x == Occupation::Rolling)
-                        .count()
-            })
-            .sum::<usize>() as u64
+    fn line(&self, index: usize) -> &[u64] {
+        &self.bits[index * self.words..(index + 1) * self.words]
     }
 
-    fn get(&self, coords: UCoords) -> Option<Occupation> {
-        let index = coords.y * self.size.x + coords.x;
-        self.occupation.get(index).copied()
+    fn line_mut(&mut self, index: usize) -> &mut [u64] {
+        &mut self.bits[index * self.words..(index + 1) * self.words]
     }
 
-    fn set(&mut self, coords: UCoords, value: Occupation) {
-        let index = coords.y * self.size.x + coords.x;
-        self.occupation[index] = value;
+    fn set(&mut self, line: usize, index: usize) {
+        self.line_mut(line)[index / 64] |= 1 << (index % 64);
     }
 
-    /// Computes a hash of the platform's current state using the default algorithm.
-    fn hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::default();
-        self.occupation.hash(&mut hasher);
-        hasher.finish()
+    /// Swaps the lines and the indices, one block of 64×64 bits at a time.
+    fn transpose(&self) -> BitMatrix {
+        let mut transposed = BitMatrix::new(self.side());
+        let mut block = [0; 64];
+
+        for i in 0..self.words {
+            for j in 0..self.words {
+                for (k, word) in block.iter_mut().enumerate() {
+                    *word = self.line(i * 64 + k)[j];
+                }
+
+                transpose_block(&mut block);
+
+                for (k, &word) in block.iter().enumerate() {
+                    transposed.line_mut(j * 64 + k)[i] = word;
+                }
+            }
+        }
+
+        transposed
     }
 }
 
-impl From<u8> for Occupation {
-    fn from(value: u8) -> Self {
+/* == Functions == */
+
+/// Iterates over the words of a line that hold bits from `start` to `end`,
+/// with the mask of those bits in each word.
+fn words(start: usize, end: usize) -> impl Iterator<Item = (usize, u64)> {
+    let (first, last) = (start / 64, end.saturating_sub(1) / 64);
+
+    (first..=last)
+        .filter(move |_| start < end)
+        .map(move |word| {
+            let low = if word == first { start % 64 } else { 0 };
+            let high = if word == last { (end - 1) % 64 + 1 } else { 64 };
+            (word, (u64::MAX >> (64 - (high - low))) << low)
+        })
+}
+
+/// Counts the bits set in a line from `start` to `end`.
+fn count_ones(line: &[u64], start: usize, end: usize) -> usize {
+    words(start, end)
+        .map(|(word, mask)| (line[word] & mask).count_ones() as usize)
+        .sum()
+}
+
+/// Iterates over the indices of the bits set in a word.
+fn bits(mut word: u64) -> impl Iterator<Item = usize> {
+    iter::from_fn(move || {
+        (word != 0).then(|| {
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            bit
+        })
+    })
+}
+
+/// Sets the bits of a line from `start` to `end`.
+fn fill(line: &mut [u64], start: usize, end: usize) {
+    for (word, mask) in words(start, end) {
+        line[word] |= mask;
+    }
+}
+
+/// Transposes a block of 64×64 bits in place, by swapping the off-diagonal
+/// quarters of blocks of halving sizes, from the whole block down to 2×2.
+fn transpose_block(block: &mut [u64; 64]) {
+    let mut width = 32;
+    let mut mask: u64 = 0x0000_0000_FFFF_FFFF;
+
+    while width != 0 {
+        let mut k = 0;
+
+        while k < 64 {
+            let swap = ((block[k] >> width) ^ block[k + width]) & mask;
+            block[k] ^= swap << width;
+            block[k + width] ^= swap;
+            k = (k + width + 1) & !width;
+        }
+
+        width >>= 1;
+        mask ^= mask << width;
+    }
+}
+
+/// Mixes a number into a hash, like the hash function of rustc.
+fn combine(hash: u64, count: usize) -> u64 {
+    (hash.rotate_left(5) ^ count as u64).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95)
+}
+
+/* == Trait implementations == */
+
+impl TryFrom<u8> for Occupation {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'.' => Occupation::Empty,
-            b'#' => Occupation::Fixed,
-            b'O' => Occupation::Rolling,
-            _ => unreachable!(),
+            b'.' => Ok(Occupation::Empty),
+            b'#' => Ok(Occupation::Fixed),
+            b'O' => Ok(Occupation::Rolling),
+            _ => Err(()),
         }
     }
 }
 
+impl PartialEq for Rocks {
+    fn eq(&self, other: &Self) -> bool {
+        self.lines == other.lines && self.matrix == other.matrix
+    }
+}
+
+impl Eq for Rocks {}
+
+/// Only the hash of the numbers of rocks is fed to the hasher, which is much
+/// cheaper than hashing every word of the matrix. Equal matrices along the
+/// same lines have the same numbers of rocks in each segment, so they also
+/// have the same hash.
+impl Hash for Rocks {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
 /* == Tests == */
 
 #[cfg(test)]
@@ -286,4 +437,49 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(64));
     }
+
+    #[test]
+    fn test_transpose() {
+        let mut matrix = BitMatrix::new(100);
+        let cells = [(0, 0), (0, 99), (3, 70), (64, 1), (99, 63), (42, 42)];
+
+        for (line, index) in cells {
+            matrix.set(line, index);
+        }
+
+        let mut expected = BitMatrix::new(100);
+
+        for (line, index) in cells {
+            expected.set(index, line);
+        }
+
+        assert_eq!(matrix.transpose(), expected);
+        assert_eq!(matrix.transpose().transpose(), matrix);
+    }
+
+    #[test]
+    fn test_north_beam_load() {
+        let (platform, rocks) = Platform::parse_str(&read_example(DAY)).unwrap();
+        let tilted = platform.tilt(&rocks, Direction::North);
+
+        let rows = Rocks {
+            matrix: tilted.matrix.transpose(),
+            lines: Lines::Rows,
+            ..tilted.clone()
+        };
+
+        assert_eq!(tilted.lines, Lines::Columns);
+        assert_eq!(platform.north_beam_load(&tilted), 136);
+        assert_eq!(platform.north_beam_load(&rows), 136);
+    }
+
+    #[test]
+    fn test_hash() {
+        let (platform, rocks) = Platform::parse_str(&read_example(DAY)).unwrap();
+        let spun = platform.spin(&rocks);
+
+        assert_eq!(spun.lines, Lines::Rows);
+        assert_eq!(spun.hash, platform.rows.hash(&spun.matrix));
+        assert_ne!(spun.hash, rocks.hash);
+    }
 }
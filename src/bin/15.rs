@@ -1,7 +1,12 @@
 advent_of_code::solution!(15);
 
-use std::collections::HashMap;
-
+use advent_of_code::{
+    tools::{
+        atom::{Atom, AtomTable},
+        parse::{lines, Span},
+    },
+    ParseErrorKind, ParseResult, Report,
+};
 use itertools::Itertools;
 
 /* == Definitions == */
@@ -21,7 +26,7 @@ struct Instruction<'a> {
 
 #[derive(Clone)]
 struct Lens {
-    atom: Atom,
+    atom: Atom<Label>,
     focal_length: u8,
 }
 
@@ -30,43 +35,34 @@ enum Operation {
     Subtract,
 }
 
-/// Lookup table for atoms associated with string labels.
-struct AtomTable<'a> {
-    counter: u32,
-    labels: HashMap<&'a str, Atom>,
-}
-
-/// Represents a string label as a unique integer instead, with the
-/// mapping stored in `AtomTable`. Allows for fast comparison without
-/// heap-allocated strings.
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct Atom(u32, u8);
+/// Tags the atoms of lens labels, which are interned to compare them without
+/// comparing strings.
+enum Label {}
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let result = input
-        .lines()
-        .flat_map(|line| line.split(','))
-        .map(|line| hash(line) as u32)
+    let result = lines(input)
+        .flat_map(|line| line.split(","))
+        .map(|step| hash(step.as_str()) as u32)
         .sum();
 
     Some(result)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut atoms = AtomTable::new();
+    let mut atoms = AtomTable::<&str, Label>::default();
     let mut boxes = vec![LensBox::new(); NUMBER_BOXES];
 
-    let instructions = input
-        .lines()
-        .flat_map(|line| line.split(','))
+    let instructions = lines(input)
+        .flat_map(|line| line.split(","))
         .map(parse_instruction);
 
     for instruction in instructions {
+        let instruction = instruction.report()?;
         let box_index = hash(instruction.label) as usize;
         let bx = &mut boxes[box_index];
-        let atom = atoms.get_label(instruction.label);
+        let atom = atoms.create(instruction.label);
 
         match instruction.operation {
             Operation::Add(focal_length) => bx.add_lens(Lens { atom, focal_length }),
@@ -85,20 +81,24 @@ pub fn part_two(input: &str) -> Option<u32> {
 
 /* == Input parsing == */
 
-fn parse_instruction(instruction: &str) -> Instruction {
-    let index = instruction.find(|x| x == '=' || x == '-').unwrap();
+fn parse_instruction(instruction: Span) -> ParseResult<Instruction> {
+    let index = instruction.as_str().find(['=', '-']).ok_or_else(|| {
+        let kind = ParseErrorKind::Expected("`=` or `-`");
+        instruction.error_at(instruction.len(), kind)
+    })?;
+
     let (label, op_str) = instruction.split_at(index);
-    let mut op_iterator = op_str.bytes();
+    let label = label.as_str();
 
-    match op_iterator.next().unwrap() {
-        b'=' => Instruction {
+    match op_str.byte(0)? {
+        b'=' => Ok(Instruction {
             label,
-            operation: Operation::Add(op_iterator.next().unwrap() - b'0'),
-        },
-        b'-' => Instruction {
+            operation: Operation::Add(op_str.slice(1, op_str.len()).parse()?),
+        }),
+        b'-' => Ok(Instruction {
             label,
             operation: Operation::Subtract,
-        },
+        }),
         _ => unreachable!(),
     }
 }
@@ -120,7 +120,7 @@ impl LensBox {
     }
 
     /// Removes a lens from a table.
-    fn remove_lens(&mut self, atom: &Atom) {
+    fn remove_lens(&mut self, atom: &Atom<Label>) {
         if let Some((i, _)) = self.lenses.iter().find_position(|l| l.atom == *atom) {
             self.lenses.remove(i);
         }
@@ -136,29 +136,6 @@ impl LensBox {
     }
 }
 
-impl<'a> AtomTable<'a> {
-    fn new() -> AtomTable<'a> {
-        AtomTable {
-            counter: 0,
-            labels: HashMap::new(),
-        }
-    }
-
-    /// Returns the atom associated with a label, or creates a new one if
-    /// the label is not yet present.
-    fn get_label<'b: 'a>(&mut self, label: &'b str) -> Atom {
-        match self.labels.get(label) {
-            Some(label) => *label,
-            None => {
-                self.counter += 1;
-                let atom = Atom(self.counter, hash(label));
-                self.labels.insert(label, atom);
-                atom
-            }
-        }
-    }
-}
-
 /* == Utility == */
 
 fn hash(input: &str) -> u8 {
@@ -1,26 +1,18 @@
 advent_of_code::solution!(16);
 
-use std::{
-    collections::HashSet,
-    hash::{DefaultHasher, Hash, Hasher},
-    mem,
-};
+use std::{num::NonZeroUsize, thread};
 
-use advent_of_code::tools::*;
+use advent_of_code::{tools::*, ParseResult, Report};
 
 /* == Definitions == */
 
 struct Map {
-    size: UCoords,
-    tiles: Vec<Tile>,
+    tiles: Grid<Tile>,
+    /// The index of each splitter, in reading order.
+    splitters: Grid<Option<usize>>,
 }
 
-struct Tile {
-    contents: TileType,
-    energised: bool,
-}
-
-enum TileType {
+enum Tile {
     Empty,
     Splitter(SplitterOrientation),
     Mirror(MirrorOrientation),
@@ -40,100 +32,155 @@ enum MirrorOrientation {
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 struct Beam {
     origin: UCoords,
-    direction: Coords,
+    direction: Direction,
+}
+
+/// A set of tiles of the map, with a bit for each tile.
+#[derive(Clone, PartialEq, Eq)]
+struct TileSet {
+    words: Vec<u64>,
+}
+
+/// The tiles energised by a beam until it is split, and the splitter that
+/// splits it, if any.
+struct Segment {
+    tiles: TileSet,
+    next: Option<usize>,
+}
+
+/// The map with the two segments leaving each splitter, which are traced once
+/// and then reused by every beam that reaches the splitter.
+struct Contraption {
+    map: Map,
+    segments: Vec<[Segment; 2]>,
 }
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let result = solve_beam(
-        &mut Map::parse_str(input),
-        Beam {
-            origin: UCoords::new(0, 0),
-            direction: Coords::new(1, 0),
-        },
-    );
+    let contraption = Contraption::new(Map::parse_str(input).report()?);
 
-    Some(result)
+    Some(contraption.energised(Beam {
+        origin: UCoords::new(0, 0),
+        direction: Direction::East,
+    }))
 }
 
+/// The edge beams are shared between threads, which all reuse the same
+/// segments between splitters.
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut map = Map::parse_str(input);
+    let contraption = Contraption::new(Map::parse_str(input).report()?);
+    let beams: Vec<_> = starting_beams(&contraption.map.tiles.size()).collect();
+
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let chunk_size = beams.len().div_ceil(threads).max(1);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = beams
+            .chunks(chunk_size)
+            .map(|chunk| {
+                let contraption = &contraption;
+                scope.spawn(move || chunk.iter().map(|&beam| contraption.energised(beam)).max())
+            })
+            .collect();
 
-    starting_beams(&map.size.clone())
-        .map(|b| {
-            map.reset();
-            solve_beam(&mut map, b)
-        })
-        .max()
+        workers
+            .into_iter()
+            .filter_map(|worker| worker.join().unwrap())
+            .max()
+    })
 }
 
-fn solve_beam(map: &mut Map, beam: Beam) -> u32 {
-    let mut cache = HashSet::new();
-
-    let mut beams = vec![beam];
-
-    while let Some(mut beam) = beams.pop() {
-        let hash = beam.hash();
-
-        if cache.contains(&hash) {
-            continue;
-        } else {
-            cache.insert(hash);
-        }
+/* == Implementations == */
 
-        map.get_mut(&beam.origin).unwrap().energised = true;
+impl Map {
+    fn parse_str(input: &str) -> ParseResult<Map> {
+        let tiles: Grid<Tile> = Grid::parse_with(input, "a tile", |b| b.try_into().ok())?;
+
+        let mut count = 0;
+        let splitters = tiles.map(|tile| {
+            matches!(tile, Tile::Splitter(_)).then(|| {
+                count += 1;
+                count - 1
+            })
+        });
 
-        let new_beam = beam.process_tile(map.get(&beam.origin).unwrap());
+        Ok(Map { tiles, splitters })
+    }
 
-        if beam.move_beam(&map.size) {
-            beams.push(beam);
-        }
+    /// Follows a beam, which has yet to go through the tile at its origin,
+    /// until it is split or leaves the map.
+    fn trace(&self, mut beam: Beam) -> Segment {
+        let cells = self.tiles.cells().len();
+        let mut tiles = TileSet::new(cells);
+
+        // Without splitters, a beam can only go through each tile once in each
+        // direction, unless it is stuck in a loop of mirrors
+        for _ in 0..4 * cells {
+            tiles.insert(self.tiles.index_of(beam.origin));
+
+            if beam.process_tile(&self.tiles[beam.origin]).is_some() {
+                return Segment {
+                    tiles,
+                    next: self.splitters[beam.origin],
+                };
+            }
 
-        if let Some(mut new_beam) = new_beam {
-            if new_beam.move_beam(&map.size) {
-                beams.push(new_beam);
+            if !beam.move_beam(&self.tiles.size()) {
+                break;
             }
         }
-    }
 
-    map.tiles.iter().filter(|t| t.energised).count() as u32
+        Segment { tiles, next: None }
+    }
 }
 
-/* == Implementations == */
-
-impl Map {
-    fn parse_str(input: &str) -> Map {
-        let mut height = 0;
-
-        let tiles: Vec<Tile> = input
-            .lines()
-            .flat_map(|line| {
-                height += 1;
-                line.bytes().map(|b| b.into())
+impl Contraption {
+    /// Traces the segments leaving every splitter, in both directions.
+    fn new(map: Map) -> Self {
+        let segments = (map.splitters.iter())
+            .filter(|(_, index)| index.is_some())
+            .map(|(origin, _)| {
+                let direction = match map.tiles[origin] {
+                    Tile::Splitter(SplitterOrientation::Vertical) => Direction::East,
+                    _ => Direction::North,
+                };
+
+                let mut beam = Beam { origin, direction };
+                let split = beam.process_tile(&map.tiles[origin]).unwrap();
+
+                [beam, split].map(|mut beam| match beam.move_beam(&map.tiles.size()) {
+                    true => map.trace(beam),
+                    false => Segment {
+                        tiles: TileSet::new(map.tiles.cells().len()),
+                        next: None,
+                    },
+                })
             })
             .collect();
 
-        Map {
-            size: UCoords::new(tiles.len() / height, height),
-            tiles,
-        }
+        Contraption { map, segments }
     }
 
-    fn get(&self, coords: &UCoords) -> Option<&Tile> {
-        let index = coords.y * self.size.x + coords.x;
-        self.tiles.get(index)
-    }
+    /// Counts the tiles energised by a beam, which are those of its first
+    /// segment and of the segments leaving every splitter that it reaches.
+    fn energised(&self, beam: Beam) -> u32 {
+        let Segment { mut tiles, next } = self.map.trace(beam);
+        let mut visited = vec![false; self.segments.len()];
+        let mut splitters: Vec<usize> = next.into_iter().collect();
 
-    fn get_mut(&mut self, coords: &UCoords) -> Option<&mut Tile> {
-        let index = coords.y * self.size.x + coords.x;
-        self.tiles.get_mut(index)
-    }
+        while let Some(splitter) = splitters.pop() {
+            if std::mem::replace(&mut visited[splitter], true) {
+                continue;
+            }
 
-    fn reset(&mut self) {
-        for tile in self.tiles.iter_mut() {
-            tile.energised = false;
+            for segment in &self.segments[splitter] {
+                tiles.union_with(&segment.tiles);
+                splitters.extend(segment.next);
+            }
         }
+
+        tiles.len()
     }
 }
 
@@ -142,26 +189,26 @@ impl Beam {
     /// direction if necessary, also returning an optional new secondary
     /// beam if a beam splitter is encountered that should also be processed.
     fn process_tile(&mut self, tile: &Tile) -> Option<Beam> {
-        match &tile.contents {
-            TileType::Empty => None,
+        match tile {
+            Tile::Empty => None,
 
-            TileType::Mirror(orientation) => {
-                mem::swap(&mut self.direction.x, &mut self.direction.y);
+            Tile::Mirror(orientation) => {
+                // A `/` mirror turns eastward beams to the north, `\` to the south
+                let turn_left = *orientation == MirrorOrientation::NorthEast;
 
-                if *orientation == MirrorOrientation::NorthEast {
-                    self.direction.x *= -1;
-                    self.direction.y *= -1;
-                }
+                self.direction = match self.direction.is_horizontal() == turn_left {
+                    true => self.direction.turn_left(),
+                    false => self.direction.turn_right(),
+                };
 
                 None
             }
 
-            TileType::Splitter(direction) if self.should_split(direction) => {
-                mem::swap(&mut self.direction.x, &mut self.direction.y);
+            Tile::Splitter(direction) if self.should_split(direction) => {
                 let mut new_beam = *self;
 
-                new_beam.direction.x *= -1;
-                new_beam.direction.y *= -1;
+                self.direction = self.direction.turn_left();
+                new_beam.direction = new_beam.direction.turn_right();
 
                 Some(new_beam)
             }
@@ -172,15 +219,15 @@ impl Beam {
 
     fn should_split(&self, tile: &SplitterOrientation) -> bool {
         match tile {
-            SplitterOrientation::Vertical => self.direction.x != 0,
-            SplitterOrientation::Horizontal => self.direction.y != 0,
+            SplitterOrientation::Vertical => self.direction.is_horizontal(),
+            SplitterOrientation::Horizontal => self.direction.is_vertical(),
         }
     }
 
     /// Moves the beam one step in the current direction, returning
     /// true if the beam is still in the map bounds.
     fn move_beam(&mut self, bounds: &UCoords) -> bool {
-        let new_coords = Coords::from(self.origin) + self.direction;
+        let new_coords = Coords::from(self.origin) + self.direction.coords();
 
         match new_coords.ucoords(bounds) {
             Some(new_ucoords) => {
@@ -190,12 +237,27 @@ impl Beam {
             None => false,
         }
     }
+}
+
+impl TileSet {
+    fn new(len: usize) -> Self {
+        TileSet {
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
 
-    fn hash(&self) -> u64 {
-        let mut hasher = DefaultHasher::default();
-        self.origin.hash(&mut hasher);
-        self.direction.hash(&mut hasher);
-        hasher.finish()
+    fn insert(&mut self, index: usize) {
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+
+    fn union_with(&mut self, other: &TileSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    fn len(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
     }
 }
 
@@ -204,10 +266,10 @@ impl Beam {
 /// Returns an iterator for all possible beam starting positions at the
 /// edges of the map.
 fn starting_beams(size: &UCoords) -> impl Iterator<Item = Beam> + '_ {
-    let top_row = (0..size.x).map(|x| ((x, 0), (0, 1)));
-    let bottom_row = (0..size.x).map(|x| ((x, size.y - 1), (0, -1)));
-    let left_column = (0..size.y).map(|y| ((0, y), (1, 0)));
-    let right_column = (0..size.y).map(|y| ((size.x - 1, y), (-1, 0)));
+    let top_row = (0..size.x).map(|x| ((x, 0), Direction::South));
+    let bottom_row = (0..size.x).map(|x| ((x, size.y - 1), Direction::North));
+    let left_column = (0..size.y).map(|y| ((0, y), Direction::East));
+    let right_column = (0..size.y).map(|y| ((size.x - 1, y), Direction::West));
 
     top_row
         .chain(bottom_row)
@@ -215,26 +277,23 @@ fn starting_beams(size: &UCoords) -> impl Iterator<Item = Beam> + '_ {
         .chain(right_column)
         .map(|(origin, direction)| Beam {
             origin: origin.into(),
-            direction: direction.into(),
+            direction,
         })
 }
 
 /* == Trait implementations == */
 
-impl From<u8> for Tile {
-    fn from(value: u8) -> Self {
-        let contents = match value {
-            b'.' => TileType::Empty,
-            b'/' => TileType::Mirror(MirrorOrientation::NorthEast),
-            b'\\' => TileType::Mirror(MirrorOrientation::NorthWest),
-            b'-' => TileType::Splitter(SplitterOrientation::Horizontal),
-            b'|' => TileType::Splitter(SplitterOrientation::Vertical),
-            _ => panic!(),
-        };
-
-        Tile {
-            contents,
-            energised: false,
+impl TryFrom<u8> for Tile {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'.' => Ok(Tile::Empty),
+            b'/' => Ok(Tile::Mirror(MirrorOrientation::NorthEast)),
+            b'\\' => Ok(Tile::Mirror(MirrorOrientation::NorthWest)),
+            b'-' => Ok(Tile::Splitter(SplitterOrientation::Horizontal)),
+            b'|' => Ok(Tile::Splitter(SplitterOrientation::Vertical)),
+            _ => Err(()),
         }
     }
 }
@@ -255,4 +314,21 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(51));
     }
+
+    #[test]
+    fn test_segments() {
+        let contraption = Contraption::new(Map::parse_str(&read_example(DAY)).unwrap());
+
+        // The beam from the top left corner goes east until the `|` at (1, 0)
+        let first = contraption.map.trace(Beam {
+            origin: UCoords::new(0, 0),
+            direction: Direction::East,
+        });
+
+        assert_eq!(first.tiles.len(), 2);
+        assert_eq!(first.next, contraption.map.splitters[UCoords::new(1, 0)]);
+
+        // Every splitter is split in both directions
+        assert_eq!(contraption.segments.len(), 13);
+    }
 }
@@ -1,209 +1,187 @@
 advent_of_code::solution!(17);
 
-use std::{cmp::Ordering, collections::BinaryHeap, ops::Range};
+use std::ops::Range;
 
-use advent_of_code::tools::*;
+use advent_of_code::{
+    tools::{
+        search::{BucketQueue, DenseMap, Search},
+        *,
+    },
+    ParseResult, Report,
+};
 
 /* == Definitions == */
 
-const DIRECTIONS: usize = 4;
 const CRUCIBLE_RANGE: Range<u8> = 0..4;
 const ULTRA_CRUCIBLE_RANGE: Range<u8> = 4..11;
 
 struct City {
-    blocks: Vec<u8>,
-    size: UCoords,
+    blocks: Grid<u8>,
 }
 
-/// A branch is a possible path through the city, with a position, direction
-/// and length. The loss is the amount of heat lost following this path.
-#[derive(Clone)]
-struct Branch {
-    direction: Coords,
-    length: u8,
-    loss: u32,
-    position: UCoords,
+/// The axis along which a crucible entered a block, which it has to leave
+/// along the other one.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Axis {
+    Horizontal,
+    Vertical,
 }
 
-/// Fast storage of visited branch states in a linear array. Uses a 16
-/// bit integer storing each possible length good cache locality.
-/// The maximum crucible length is therefore 15.
-struct VisitationMatrix {
-    map: Vec<u16>,
-    step: usize,
+/// The order in which the search explores the states.
+#[derive(Clone, Copy, Debug)]
+enum Heuristic {
+    /// By heat loss only, with Dijkstra's algorithm, which the tests
+    /// compare against.
+    #[cfg_attr(not(test), allow(dead_code))]
+    Dijkstra,
+    /// By heat loss plus a lower bound of the loss left until the factory,
+    /// with A*.
+    AStar,
 }
 
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u32> {
-    solve(input, CRUCIBLE_RANGE)
+    solve(input, CRUCIBLE_RANGE, Heuristic::AStar).report()?
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    solve(input, ULTRA_CRUCIBLE_RANGE)
+    solve(input, ULTRA_CRUCIBLE_RANGE, Heuristic::AStar).report()?
 }
 
-/// Solves the problem by exploring the city with a greedy algorithm,
-/// similar to the dynamic programming approach used in Day 12.
-fn solve(input: &str, turn_range: Range<u8>) -> Option<u32> {
-    let city = parse_input(input);
-    let end = UCoords::new(city.size.x - 1, city.size.y - 1);
-
-    let mut visit_map = VisitationMatrix::new(&city.size);
-    let mut heap = BinaryHeap::from_iter(city.starting_vectors());
+/* == Input parsing == */
 
-    while let Some(branch) = heap.pop() {
-        for new_branch in branch.next_branches(&city, &turn_range) {
-            if new_branch.position == end {
-                if new_branch.length >= turn_range.start {
-                    return Some(new_branch.loss);
-                }
+fn parse_input(input: &str) -> ParseResult<City> {
+    let blocks = Grid::parse_with(input, "a digit", |c| c.is_ascii_digit().then_some(c - b'0'))?;
 
-                continue;
-            }
-
-            if visit_map.visit(
-                &new_branch.position,
-                &new_branch.direction,
-                new_branch.length,
-            ) {
-                heap.push(new_branch);
-            }
-        }
-    }
-
-    panic!("No solution found!");
+    Ok(City { blocks })
 }
 
-/* == Input parsing == */
+/* == Functions == */
 
-fn parse_input(input: &str) -> City {
-    let mut height = 0;
+/// Finds the least heat loss from the top-left block to the bottom-right one,
+/// where the crucible moves in straight lines of a length in `turn_range`
+/// before turning. Both heuristics find the same heat loss.
+fn solve(input: &str, turn_range: Range<u8>, heuristic: Heuristic) -> ParseResult<Option<u32>> {
+    let city = parse_input(input)?;
 
-    let blocks = input
-        .lines()
-        .inspect(|_| height += 1)
-        .flat_map(|line| line.bytes().map(|b| b - b'0').collect::<Vec<_>>())
-        .collect::<Vec<_>>();
+    let bounds = match heuristic {
+        Heuristic::Dijkstra => vec![0; city.blocks.cells().len()],
+        Heuristic::AStar => city.lower_bounds(),
+    };
 
-    let size = UCoords::new(blocks.len() / height, height);
-    City { blocks, size }
+    Ok(city.least_heat_loss(&turn_range, &bounds))
 }
 
 /* == Implementations == */
 
 impl City {
-    fn get(&self, coords: &UCoords) -> Option<u8> {
-        let index = coords.x + coords.y * self.size.x;
-        self.blocks.get(index).copied()
+    /// Searches with Dijkstra's algorithm, or A* with non-zero bounds, where a
+    /// move is a whole straight line followed by a turn. The state is then
+    /// only the position and the axis of the last line, without the length
+    /// or the direction, so the best heat loss of every state fits in a dense
+    /// table of two entries per block.
+    ///
+    /// Each line loses at most 9 per block, so the priorities of the pending
+    /// states span a small range and are kept in a [`BucketQueue`].
+    fn least_heat_loss(&self, turn_range: &Range<u8>, bounds: &[u32]) -> Option<u32> {
+        let end = self.blocks.cells().len() - 1;
+        let states = 2 * self.blocks.cells().len();
+        let index = |&(block, axis): &(usize, Axis)| axis.state(block);
+
+        // The starting block has not been entered yet, so it can be left in
+        // any direction
+        let found = Search::with_map(DenseMap::new(states, index)).astar_buckets(
+            [(0, Axis::Horizontal), (0, Axis::Vertical)],
+            |&(block, axis)| self.lines(block, axis, turn_range),
+            |&(block, _)| bounds[block] as usize,
+            |&(block, _)| block == end,
+        )?;
+
+        Some(found.cost as u32)
     }
 
-    fn starting_vectors(&self) -> impl Iterator<Item = Branch> + '_ {
-        [(0, 1), (1, 0)].into_iter().map(|(x, y)| {
-            let position = UCoords::new(x as usize, y as usize);
-
-            Branch {
-                direction: Coords::new(x, y),
-                position,
-                length: 1,
-                loss: self.get(&position).unwrap() as u32,
-            }
-        })
-    }
-}
-
-impl Branch {
-    /// Returns an iterator of next possible branches, given the city map and the
-    /// state of the current branch.
-    fn next_branches<'a>(
+    /// Returns the blocks where a crucible that entered a block along an axis
+    /// can turn next, with the heat lost along the way.
+    fn lines<'a>(
         &'a self,
-        city: &'a City,
-        turn_range: &Range<u8>,
-    ) -> impl Iterator<Item = Branch> + 'a {
-        let Coords { x, y } = self.direction;
-
-        let straight_branch = (self.length + 1 < turn_range.end)
-            .then_some(())
-            .map(|_| (self.length + 1, self.direction));
-
-        let lateral_branches = (self.length >= turn_range.start)
-            .then_some(())
-            .into_iter()
-            .flat_map(move |_| [(y, x), (-y, -x)].map(|(x, y)| (1, Coords::new(x, y))));
-
-        lateral_branches
-            .chain(straight_branch)
-            .flat_map(|(length, direction)| unsafe {
-                let position = (direction + self.position.into()).ucoords(&city.size)?;
-                let loss = self.loss + city.get(&position).unwrap_unchecked() as u32;
-
-                Some(Branch {
-                    length,
-                    direction,
-                    position,
-                    loss,
+        block: usize,
+        axis: Axis,
+        turn_range: &'a Range<u8>,
+    ) -> impl Iterator<Item = ((usize, Axis), usize)> + 'a {
+        axis.turns().into_iter().flat_map(move |direction| {
+            let mut position = self.blocks.coords_of(block);
+            let mut loss = 0;
+
+            (1..turn_range.end)
+                .map_while(move |length| {
+                    position = self.blocks.step(position, direction)?;
+                    loss += self.blocks[position] as usize;
+                    Some((length, self.blocks.index_of(position), loss))
                 })
-            })
-    }
-}
-
-impl VisitationMatrix {
-    fn new(size: &UCoords) -> Self {
-        Self {
-            map: vec![0; size.x * size.y * DIRECTIONS],
-            step: size.x,
-        }
+                .filter(|&(length, ..)| length >= turn_range.start)
+                .map(move |(_, next, loss)| ((next, Axis::of(direction)), loss))
+        })
     }
 
-    fn visit(&mut self, at: &UCoords, direction: &Coords, length: u8) -> bool {
-        debug_assert!(length < 16);
+    /// Computes the least heat loss from each block to the bottom-right one,
+    /// ignoring how the crucible has to turn, with Dijkstra's algorithm from
+    /// the bottom-right block. Any path of the crucible loses at least as much,
+    /// so this is a consistent heuristic for A*.
+    fn lower_bounds(&self) -> Vec<u32> {
+        let end = self.blocks.cells().len() - 1;
+        let mut bounds = vec![u32::MAX; self.blocks.cells().len()];
+        let mut queue = BucketQueue::new();
 
-        let index = at.y * 4 * self.step + at.x * 4 + direction_id(direction);
+        bounds[end] = 0;
+        queue.push(0, end);
 
-        // Fetch the bit, set it and return whether it was not set before
-        let mask = 1 << length;
-        let bits = self.map[index];
-        self.map[index] = bits | mask;
+        while let Some((loss, block)) = queue.pop() {
+            if loss > bounds[block] as usize {
+                continue;
+            }
 
-        (bits & mask) == 0
-    }
-}
+            // Entering this block from a neighbour loses its heat
+            let loss = bounds[block] + self.blocks.cells()[block] as u32;
 
-fn direction_id(direction: &Coords) -> usize {
-    match direction {
-        Coords { x: 0, y: 1 } => 0,
-        Coords { x: 1, y: 0 } => 1,
-        Coords { x: 0, y: -1 } => 2,
-        Coords { x: -1, y: 0 } => 3,
-        _ => unreachable!(),
-    }
-}
+            for neighbour in self.blocks.neighbours(self.blocks.coords_of(block)) {
+                let neighbour = self.blocks.index_of(neighbour);
 
-/* == Trait implementations == */
+                if loss < bounds[neighbour] {
+                    bounds[neighbour] = loss;
+                    queue.push(loss as usize, neighbour);
+                }
+            }
+        }
 
-impl PartialEq for Branch {
-    fn eq(&self, other: &Self) -> bool {
-        self.loss == other.loss
+        bounds
     }
 }
 
-impl Eq for Branch {}
+impl Axis {
+    fn of(direction: Direction) -> Axis {
+        match direction.is_horizontal() {
+            true => Axis::Horizontal,
+            false => Axis::Vertical,
+        }
+    }
 
-impl PartialOrd for Branch {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(other.loss.cmp(&self.loss))
+    /// Returns the index of the state of a block in the dense table.
+    fn state(self, block: usize) -> usize {
+        2 * block + self as usize
     }
-}
 
-impl Ord for Branch {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.loss.cmp(&self.loss)
+    /// Returns the two directions perpendicular to the axis.
+    fn turns(self) -> [Direction; 2] {
+        match self {
+            Axis::Horizontal => [Direction::North, Direction::South],
+            Axis::Vertical => [Direction::East, Direction::West],
+        }
     }
 }
 
 /* == Tests == */
 
-/* == Tests == */
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +204,43 @@ mod tests {
         let result = part_two(&read_example_part(DAY, 2));
         assert_eq!(result, Some(71));
     }
+
+    #[test]
+    fn test_heuristics_on_example() {
+        let input = read_example(DAY);
+
+        for range in [CRUCIBLE_RANGE, ULTRA_CRUCIBLE_RANGE] {
+            let dijkstra = solve(&input, range.clone(), Heuristic::Dijkstra).unwrap();
+            let astar = solve(&input, range, Heuristic::AStar).unwrap();
+
+            assert_eq!(dijkstra, astar);
+        }
+    }
+
+    #[test]
+    fn test_heuristics_on_large_city() {
+        // A larger city with pseudo-random heat losses
+        let mut seed = 17u64;
+        let input: String = (0..150)
+            .map(|_| {
+                let row: String = (0..200)
+                    .map(|_| {
+                        seed = seed
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        char::from(b'1' + (seed >> 33) as u8 % 9)
+                    })
+                    .collect();
+                row + "\n"
+            })
+            .collect();
+
+        for range in [CRUCIBLE_RANGE, ULTRA_CRUCIBLE_RANGE] {
+            let dijkstra = solve(&input, range.clone(), Heuristic::Dijkstra).unwrap();
+            let astar = solve(&input, range, Heuristic::AStar).unwrap();
+
+            assert!(dijkstra.is_some());
+            assert_eq!(dijkstra, astar);
+        }
+    }
 }
@@ -1,6 +1,14 @@
 advent_of_code::solution!(18);
 
-use advent_of_code::tools::{algorithms::enclosed_area, *};
+use advent_of_code::{
+    tools::{
+        algorithms::covered_cells,
+        parse::{lines, Span},
+        *,
+    },
+    ParseErrorKind, ParseResult, Report,
+};
+use itertools::process_results;
 
 /* == Definitions == */
 
@@ -9,100 +17,78 @@ struct Instruction {
     length: u32,
 }
 
-enum Direction {
-    Down,
-    Left,
-    Right,
-    Up,
-}
-
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u64> {
-    Some(solve(input, parse_instruction_1))
+    solve(input, parse_instruction_1).report()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    Some(solve(input, parse_instruction_2))
+    solve(input, parse_instruction_2).report()
 }
 
-/// Re-uses the shoelace formula from Day 10 to compute the area of the polygon.
-fn solve(input: &str, parser: impl Fn(&str) -> Instruction) -> u64 {
-    let mut length = 0;
-
-    let path = input
-        .lines()
-        .map(parser)
-        .scan(Coords::default(), |coords, instruction| {
-            length += instruction.length as u64;
-            *coords += instruction.direction.offset(instruction.length as i64);
+/// Counts the cells of the trench and its interior with Pick's theorem, like
+/// Day 10 does for the interior of the loop.
+fn solve(input: &str, parser: impl Fn(Span) -> ParseResult<Instruction>) -> ParseResult<u64> {
+    process_results(lines(input).map(parser), |instructions| {
+        let path = instructions.scan(Coords::default(), |coords, instruction| {
+            *coords += instruction.direction * instruction.length as i64;
             Some(*coords)
         });
 
-    enclosed_area(path) + length / 2 + 1
+        covered_cells(path)
+    })
 }
 
 /* == Input parsing == */
 
-fn parse_instruction_1(line: &str) -> Instruction {
-    let (direction, length, _) = parse_line(line);
-    Instruction {
-        direction: Direction::parse_digit(direction),
-        length: length.parse().unwrap(),
-    }
+fn parse_instruction_1(line: Span) -> ParseResult<Instruction> {
+    let (direction, length, _) = parse_line(line)?;
+
+    Ok(Instruction {
+        direction: Direction::try_from(direction.byte(0)?)
+            .map_err(|_| direction.expected("`U`, `D`, `L` or `R`"))?,
+        length: length.parse()?,
+    })
 }
 
-fn parse_instruction_2(line: &str) -> Instruction {
-    let (_, _, hex) = parse_line(line);
-    let direction_byte = (hex.as_bytes()[7] as char).to_digit(16).unwrap();
+fn parse_instruction_2(line: Span) -> ParseResult<Instruction> {
+    let (_, _, hex) = parse_line(line)?;
+    let hex = hex.strip_prefix("(#")?.strip_suffix(")")?;
 
-    Instruction {
-        direction: Direction::parse_hex(direction_byte as u8),
-        length: u32::from_str_radix(&hex[2..7], 16).unwrap(),
+    if hex.len() != 6 {
+        return Err(hex.expected("six hexadecimal digits"));
     }
-}
 
-fn parse_line(line: &str) -> (u8, &str, &str) {
-    let mut parts = line.split_ascii_whitespace();
+    hex.check_bytes(|c| c.is_ascii_hexdigit(), "a hexadecimal digit")?;
+    let (length, direction) = hex.split_at(5);
 
-    (
-        parts.next().unwrap().bytes().next().unwrap(),
-        parts.next().unwrap(),
-        parts.next().unwrap(),
-    )
-}
+    let direction_digit = (direction.byte(0)? as char)
+        .to_digit(16)
+        .unwrap_or_default();
+    let length = u32::from_str_radix(length.as_str(), 16)
+        .map_err(|_| length.error(ParseErrorKind::Invalid("u32")))?;
 
-/* == Implementations == */
-
-impl Direction {
-    fn parse_digit(c: u8) -> Self {
-        match c {
-            b'U' => Self::Up,
-            b'D' => Self::Down,
-            b'L' => Self::Left,
-            b'R' => Self::Right,
-            _ => unreachable!(),
-        }
+    if direction_digit > 3 {
+        return Err(direction.expected("a direction between 0 and 3"));
     }
 
-    fn parse_hex(src: u8) -> Self {
-        match src {
-            0 => Self::Left,
-            1 => Self::Down,
-            2 => Self::Right,
-            3 => Self::Up,
-            _ => unreachable!(),
-        }
-    }
+    // The digits 0 to 3 stand for R, D, L and U, clockwise from east
+    Ok(Instruction {
+        direction: Direction::from_index(direction_digit as usize + 1),
+        length,
+    })
+}
 
-    fn offset(&self, factor: i64) -> Coords {
-        match self {
-            Self::Up => Coords::new(0, -factor),
-            Self::Down => Coords::new(0, factor),
-            Self::Left => Coords::new(-factor, 0),
-            Self::Right => Coords::new(factor, 0),
-        }
-    }
+fn parse_line(line: Span) -> ParseResult<(Span, Span, Span)> {
+    let mut parts = line.split_whitespace();
+
+    let mut next = |what| {
+        let kind = ParseErrorKind::Expected(what);
+        parts.next().ok_or_else(|| line.error_at(line.len(), kind))
+    };
+
+    Ok((next("a direction")?, next("a length")?, next("a colour")?))
 }
 
 #[cfg(test)]
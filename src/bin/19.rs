@@ -1,22 +1,31 @@
 advent_of_code::solution!(19);
 
 use std::{
-    collections::HashMap,
-    ops::{Index, IndexMut, Range},
+    ops::{Index, IndexMut},
     slice,
 };
 
-use advent_of_code::tools::atom::{Atom, AtomTable};
+use advent_of_code::{
+    tools::{
+        atom::{Atom, AtomTable, AtomVec},
+        interval::Interval,
+        parse::{lines, Span},
+    },
+    ParseError, ParseResult, Report,
+};
 use itertools::Itertools;
 
 /* == Definitions == */
 
 const FIRST_WORKFLOW: &str = "in";
 const NUMBER_CATEGORIES: usize = 4;
-const MAX_CATEGORY_RANGE: Range<u16> = 1..4001;
+const MAX_CATEGORY_RANGE: Interval<u16> = Interval {
+    start: 1,
+    end: 4001,
+};
 
 #[derive(Clone)]
-struct StatRange(Range<u16>);
+struct StatRange(Interval<u16>);
 
 #[derive(Copy, Clone)]
 enum Category {
@@ -29,21 +38,20 @@ enum Category {
 /* == Solutions == */
 
 pub fn part_one(input: &str) -> Option<u64> {
-    let (workflows, parts) = parse_input(input);
+    let (workflows, parts) = parse_input(input).report()?;
 
-    let result = parts
+    parts
         .map(|part| {
-            WorkflowIterator::new(&workflows, part)
+            Ok(WorkflowIterator::new(&workflows, part?)
                 .map(|part| part.stat_total())
-                .sum::<u64>()
+                .sum::<u64>())
         })
-        .sum();
-
-    Some(result)
+        .sum::<ParseResult<_>>()
+        .report()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let (workflows, _) = parse_input(input);
+    let (workflows, _) = parse_input(input).report()?;
 
     let result = WorkflowIterator::new(&workflows, Part::default())
         .map(|part| part.combinations())
@@ -57,23 +65,47 @@ pub fn part_two(input: &str) -> Option<u64> {
 /// Collects all workflows into a HashMap for lookup, also returning an iterator
 /// for the parts that follow. The iterator is lazy, so if the parts are not needed,
 /// they will not be parsed.
-fn parse_input(input: &str) -> (Workflows, impl Iterator<Item = Part> + '_) {
-    let mut lines = input.lines();
-
-    // Uses an atom table to store workflow tags as a unique usize for fast lookup
+///
+/// The `in` workflow and every jump target must be defined, so that parts can
+/// always be sent along without a missing workflow.
+fn parse_input(
+    input: &str,
+) -> ParseResult<(Workflows, impl Iterator<Item = ParseResult<Part>> + '_)> {
+    let mut lines = lines(input);
+    let mut count = 0;
+
+    // Uses an atom table to store workflow tags as dense indices for fast lookup
     let mut atoms = AtomTable::new();
-    let mut flows = HashMap::new();
+    let mut flows = AtomVec::new();
+    let mut jumps: Vec<(Atom, Span)> = Vec::new();
 
     while let Some(line) = lines.next() {
         if line.is_empty() {
-            return (Workflows { atoms, flows }, lines.map(Part::parse_str));
+            let first = atoms
+                .get(FIRST_WORKFLOW)
+                .filter(|&atom| is_defined(&flows, atom))
+                .ok_or_else(|| line.expected("the `in` workflow"))?;
+
+            if let Some((_, jump)) = jumps.iter().find(|(atom, _)| !is_defined(&flows, *atom)) {
+                return Err(jump.expected("a defined workflow"));
+            }
+
+            return Ok((Workflows { first, flows }, lines.map(Part::parse_str)));
         }
 
-        let (k, v) = Workflow::parse_str(line, &mut atoms);
+        let (k, v) = Workflow::parse_str(line, &mut atoms, &mut jumps)?;
         flows.insert(k, v);
+        count += 1;
     }
 
-    panic!("Expected part definitions, found EOF");
+    // Expected part definitions, found EOF
+    Err(ParseError::unexpected_end(count))
+}
+
+/// Parsed workflows always have a rule, so workflows that were only referenced
+/// are the empty default ones.
+fn is_defined(flows: &AtomVec<Workflow>, atom: Atom) -> bool {
+    flows.get(atom).is_some_and(|flow| !flow.0.is_empty())
 }
 
 /* == Workflow iterator == */
@@ -85,11 +117,11 @@ fn parse_input(input: &str) -> (Workflows, impl Iterator<Item = Part> + '_) {
 struct WorkflowIterator<'a> {
     current: Option<(Part, slice::Iter<'a, Instruction>)>,
     queue: Vec<(Part, Atom)>,
-    workflows: &'a Workflows<'a>,
+    workflows: &'a Workflows,
 }
 
 impl WorkflowIterator<'_> {
-    fn new<'a>(workflows: &'a Workflows, part: Part) -> WorkflowIterator<'a> {
+    fn new(workflows: &Workflows, part: Part) -> WorkflowIterator<'_> {
         WorkflowIterator {
             current: Some((part, workflows.first().iter())),
             queue: vec![],
@@ -144,13 +176,9 @@ impl Iterator for WorkflowIterator<'_> {
                     Some((_, Action::Reject)) | None => {}
                 },
 
-                // Each workflow should be terminated by a condition-less instruction
-                // that should consume the part, and leave the old range empty.
-                None => {
-                    if !part.is_empty() {
-                        panic!("Non-zero part range after workflow completion");
-                    }
-                }
+                // Each workflow is terminated by a condition-less instruction
+                // that consumes the part, and leaves the old range empty.
+                None => debug_assert!(part.is_empty()),
             }
         }
     }
@@ -158,33 +186,48 @@ impl Iterator for WorkflowIterator<'_> {
 
 /* == Implementations == */
 
-struct Workflows<'a> {
-    atoms: AtomTable<&'a str>,
-    flows: HashMap<Atom, Workflow>,
+/// The workflows, where the first one and every jump target are known to be
+/// defined.
+struct Workflows {
+    first: Atom,
+    flows: AtomVec<Workflow>,
 }
 
-impl Workflows<'_> {
+impl Workflows {
     fn get(&self, atom: Atom) -> &Workflow {
-        self.flows.get(&atom).unwrap()
+        &self.flows[atom]
     }
 
     fn first(&self) -> &Workflow {
-        self.get(self.atoms.get(FIRST_WORKFLOW).unwrap())
+        self.get(self.first)
     }
 }
 
+#[derive(Default)]
 struct Workflow(Vec<Instruction>);
 
 impl Workflow {
-    fn parse_str<'a>(input: &'a str, table: &mut AtomTable<&'a str>) -> (Atom, Workflow) {
-        let (tag, rest) = input.split_once('{').unwrap();
-
-        let instructions = rest[..rest.len() - 1]
-            .split(',')
-            .map(|instruction| Instruction::parse_str(instruction, table))
-            .collect();
+    /// Parses a workflow, which has to end with a rule without a condition,
+    /// also collecting the workflows that it jumps to with their spans.
+    fn parse_str<'a>(
+        input: Span<'a>,
+        table: &mut AtomTable<&'a str>,
+        jumps: &mut Vec<(Atom, Span<'a>)>,
+    ) -> ParseResult<(Atom, Workflow)> {
+        let (tag, rest) = input.split_once("{")?;
+        let rules = rest.strip_suffix("}")?;
+
+        let instructions: Vec<Instruction> = rules
+            .split(",")
+            .map(|instruction| Instruction::parse_str(instruction, table, jumps))
+            .collect::<ParseResult<_>>()?;
+
+        if instructions.last().unwrap().condition.is_some() {
+            let last = rules.split(",").last().unwrap();
+            return Err(last.expected("a rule without a condition"));
+        }
 
-        (table.create(tag), Workflow(instructions))
+        Ok((table.create(tag.as_str()), Workflow(instructions)))
     }
 
     fn iter(&self) -> slice::Iter<'_, Instruction> {
@@ -198,16 +241,20 @@ struct Instruction {
 }
 
 impl Instruction {
-    fn parse_str<'a>(instruction: &'a str, table: &mut AtomTable<&'a str>) -> Instruction {
-        match instruction.split_once(':') {
-            Some((condition, then)) => Instruction {
-                condition: Some(Predicate::parse_str(condition)),
-                action: Action::parse_str(then, table),
-            },
-            None => Instruction {
+    fn parse_str<'a>(
+        instruction: Span<'a>,
+        table: &mut AtomTable<&'a str>,
+        jumps: &mut Vec<(Atom, Span<'a>)>,
+    ) -> ParseResult<Instruction> {
+        match instruction.as_str().split_once(':') {
+            Some((condition, then)) => Ok(Instruction {
+                condition: Some(Predicate::parse_str(instruction.subspan(condition))?),
+                action: Action::parse_str(instruction.subspan(then), table, jumps),
+            }),
+            None => Ok(Instruction {
                 condition: None,
-                action: Action::parse_str(instruction, table),
-            },
+                action: Action::parse_str(instruction, table, jumps),
+            }),
         }
     }
 
@@ -223,21 +270,22 @@ impl Instruction {
                 let stat = part.category(condition.operand);
                 let new_stat = new_part.category(condition.operand);
 
-                // If the condition is met, split the ranges as necessary
-                match condition.test {
-                    PredicateTest::GreaterThan(than) if stat.end > than + 1 => {
-                        stat.end = stat.start.max(than + 1);
-                        new_stat.start = stat.start.max(than + 1);
-                    }
-
-                    PredicateTest::LessThan(than) if stat.start < than => {
-                        stat.start = stat.end.min(than);
-                        new_stat.end = stat.end.min(than);
+                // Split the range into the values that pass and fail the test
+                let (failed, passed) = match condition.test {
+                    PredicateTest::GreaterThan(than) => stat.split_at(than + 1),
+                    PredicateTest::LessThan(than) => {
+                        let (passed, failed) = stat.split_at(than);
+                        (failed, passed)
                     }
+                };
 
-                    // Condition not met, the instruction is a no-op
-                    _ => return None,
+                // Condition not met, the instruction is a no-op
+                if passed.is_empty() {
+                    return None;
                 }
+
+                *stat = failed;
+                *new_stat = passed;
             }
 
             // No condition, consume the original part completely.
@@ -262,20 +310,23 @@ enum PredicateTest {
 }
 
 impl Predicate {
-    fn parse_str(condition: &str) -> Predicate {
-        let mut chars = condition.chars();
-        let stat_char = chars.next().unwrap();
-        let op = chars.next().unwrap();
-        let amount = condition[2..].parse().unwrap();
-
-        Predicate {
-            operand: Category::from(stat_char),
-            test: match op {
-                '<' => PredicateTest::LessThan(amount),
-                '>' => PredicateTest::GreaterThan(amount),
-                _ => unreachable!(),
-            },
-        }
+    fn parse_str(condition: Span) -> ParseResult<Predicate> {
+        let (stat, rest) = condition.split_at(1.min(condition.len()));
+        let (op, amount) = rest.split_at(1.min(rest.len()));
+
+        let operand =
+            Category::try_from(stat.byte(0)?).map_err(|_| stat.expected("`x`, `m`, `a` or `s`"))?;
+
+        let test: fn(u16) -> PredicateTest = match op.byte(0)? {
+            b'<' => PredicateTest::LessThan,
+            b'>' => PredicateTest::GreaterThan,
+            _ => return Err(op.expected("`<` or `>`")),
+        };
+
+        Ok(Predicate {
+            operand,
+            test: test(amount.parse()?),
+        })
     }
 }
 
@@ -287,11 +338,19 @@ enum Action {
 }
 
 impl Action {
-    fn parse_str<'a>(action: &'a str, table: &mut AtomTable<&'a str>) -> Action {
-        match action {
+    fn parse_str<'a>(
+        action: Span<'a>,
+        table: &mut AtomTable<&'a str>,
+        jumps: &mut Vec<(Atom, Span<'a>)>,
+    ) -> Action {
+        match action.as_str() {
             "A" => Action::Accept,
             "R" => Action::Reject,
-            tag => Action::Jump(table.create(tag)),
+            tag => {
+                let atom = table.create(tag);
+                jumps.push((atom, action));
+                Action::Jump(atom)
+            }
         }
     }
 }
@@ -302,27 +361,44 @@ struct Part {
 }
 
 impl Part {
-    fn parse_str(line: &str) -> Part {
-        let (x, m, a, s) = line[1..line.len() - 1]
-            .split(',')
-            .map(|x| x[2..].parse::<u16>().unwrap().into())
+    fn parse_str(line: Span) -> ParseResult<Part> {
+        let ratings = line.strip_prefix("{")?.strip_suffix("}")?;
+
+        let (x, m, a, s) = ratings
+            .split(",")
             .collect_tuple()
-            .unwrap();
+            .ok_or_else(|| ratings.expected("four ratings"))?;
 
-        Part {
-            stats: [x, m, a, s],
-        }
+        let rating = |stat: Span, name| -> ParseResult<StatRange> {
+            let amount = stat.strip_prefix(name)?;
+            let value = amount.parse::<u16>()?;
+
+            if !MAX_CATEGORY_RANGE.contains(value) {
+                return Err(amount.expected("a rating from 1 to 4000"));
+            }
+
+            Ok(value.into())
+        };
+
+        Ok(Part {
+            stats: [
+                rating(x, "x=")?,
+                rating(m, "m=")?,
+                rating(a, "a=")?,
+                rating(s, "s=")?,
+            ],
+        })
     }
 
-    /// Returns a mutable reference to the underlying Range<u16> for the given category.
-    fn category(&mut self, category: Category) -> &mut Range<u16> {
+    /// Returns a mutable reference to the underlying interval for the given category.
+    fn category(&mut self, category: Category) -> &mut Interval<u16> {
         &mut self.stats[category as usize].0
     }
 
     /// Empties the part, setting the first range to 0..0 for speed (it's impossible to
     /// represent any valid range with the other non-zero categories).
     fn empty(&mut self) {
-        self.stats[0] = StatRange(0..0);
+        self.stats[0] = StatRange(Interval::new(0, 0));
     }
 
     fn is_empty(&self) -> bool {
@@ -346,7 +422,7 @@ impl StatRange {
     fn sum(&self) -> u64 {
         let start = self.0.start as u64;
         let end = self.0.end as u64;
-        (end * (end - 1) - start * (start - 1)) / 2
+        (end * end.saturating_sub(1) - start * start.saturating_sub(1)) / 2
     }
 }
 
@@ -354,18 +430,20 @@ impl StatRange {
 
 impl From<u16> for StatRange {
     fn from(value: u16) -> Self {
-        StatRange(value..value + 1)
+        StatRange(Interval::new(value, value + 1))
     }
 }
 
-impl From<char> for Category {
-    fn from(value: char) -> Self {
+impl TryFrom<u8> for Category {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            'x' => Category::Cool,
-            'm' => Category::Musical,
-            'a' => Category::Aerodynamic,
-            's' => Category::Shiny,
-            _ => unreachable!(),
+            b'x' => Ok(Category::Cool),
+            b'm' => Ok(Category::Musical),
+            b'a' => Ok(Category::Aerodynamic),
+            b's' => Ok(Category::Shiny),
+            _ => Err(()),
         }
     }
 }
@@ -387,7 +465,7 @@ impl<T> IndexMut<Category> for [T; NUMBER_CATEGORIES] {
 
 impl Default for StatRange {
     fn default() -> Self {
-        StatRange(MAX_CATEGORY_RANGE.clone())
+        StatRange(MAX_CATEGORY_RANGE)
     }
 }
 
@@ -409,4 +487,30 @@ mod tests {
         let result = part_two(&read_example(DAY));
         assert_eq!(result, Some(167409079868000));
     }
+
+    #[test]
+    fn test_undefined_workflows() {
+        let part = "\n\n{x=1,m=2,a=3,s=4}";
+
+        let missing_first = parse_input(&format!("px{{A}}{part}")).err().unwrap();
+        assert_eq!((missing_first.line(), missing_first.column()), (2, 1));
+
+        let missing_jump = parse_input(&format!("in{{x>10:qq,A}}{part}"))
+            .err()
+            .unwrap();
+        assert_eq!((missing_jump.line(), missing_jump.column()), (1, 9));
+
+        let no_fallback = parse_input(&format!("in{{x>10:A}}{part}")).err().unwrap();
+        assert_eq!((no_fallback.line(), no_fallback.column()), (1, 4));
+    }
+
+    #[test]
+    fn test_ratings_out_of_range() {
+        for (part, column) in [("{x=0,m=2,a=3,s=4}", 4), ("{x=1,m=2,a=3,s=65535}", 16)] {
+            let input = format!("in{{A}}\n\n{part}");
+            let (_, mut parts) = parse_input(&input).unwrap();
+            let error = parts.next().unwrap().err().unwrap();
+            assert_eq!((error.line(), error.column()), (3, column));
+        }
+    }
 }
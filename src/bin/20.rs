@@ -0,0 +1,331 @@
+advent_of_code::solution!(20);
+
+use std::collections::VecDeque;
+
+use advent_of_code::{
+    tools::{
+        atom::{Atom, AtomTable, AtomVec},
+        math::lcm_all,
+        parse::lines,
+    },
+    ParseResult, Report,
+};
+
+/* == Definitions == */
+
+const BUTTON: &str = "button";
+const BROADCASTER: &str = "broadcaster";
+const MACHINE: &str = "rx";
+const PRESSES: usize = 1000;
+
+/// Gives up on finding the periods of the counters after this many presses.
+const MAX_PRESSES: u64 = 1 << 16;
+
+enum Name {}
+
+type ModuleId = Atom<Name>;
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+enum Kind {
+    /// A module that only receives pulses, such as `output` or `rx`.
+    #[default]
+    Untyped,
+    Broadcaster,
+    FlipFlop,
+    Conjunction,
+}
+
+#[derive(Default)]
+struct Module {
+    kind: Kind,
+    outputs: Vec<ModuleId>,
+    inputs: Vec<ModuleId>,
+}
+
+struct Network<'a> {
+    names: AtomTable<&'a str, Name>,
+    modules: AtomVec<Module, Name>,
+    button: ModuleId,
+    broadcaster: ModuleId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Pulse {
+    from: ModuleId,
+    to: ModuleId,
+    high: bool,
+}
+
+/// The state of every module of a network, which changes as pulses are sent
+/// through it. Flip-flops remember whether they are on, and conjunctions the
+/// last pulse received from each of their inputs.
+struct Simulator<'n, 'a> {
+    network: &'n Network<'a>,
+    flip_flops: AtomVec<bool, Name>,
+    memory: AtomVec<Vec<bool>, Name>,
+    queue: VecDeque<Pulse>,
+}
+
+/* == Solutions == */
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let network = Network::parse_str(input).report()?;
+    let mut simulator = Simulator::new(&network);
+    let mut counts = [0; 2];
+
+    for _ in 0..PRESSES {
+        simulator.press(|pulse| counts[pulse.high as usize] += 1);
+    }
+
+    Some(counts[0] * counts[1])
+}
+
+/// The machine is fed by a single conjunction, which sends it a low pulse when
+/// all of its inputs last sent a high pulse. Each input is the output of a
+/// counter, which sends a high pulse once every period presses and is reset
+/// to low within the same press. The machine is then first turned on when all
+/// counters fire in the same press, which is the lcm of their periods.
+///
+/// The counters are assumed to start at 0, which is checked by verifying that
+/// they fire again after twice their first press.
+pub fn part_two(input: &str) -> Option<u64> {
+    let network = Network::parse_str(input).report()?;
+    let machine = network.names.get(MACHINE)?;
+
+    let [feeder] = network.modules[machine].inputs[..] else {
+        return None;
+    };
+
+    let counters = &network.modules[feeder].inputs;
+
+    if network.modules[feeder].kind != Kind::Conjunction {
+        return None;
+    }
+
+    let mut simulator = Simulator::new(&network);
+    let mut fired: Vec<Vec<u64>> = vec![Vec::new(); counters.len()];
+
+    for presses in 1..=MAX_PRESSES {
+        simulator.press(|pulse| {
+            if pulse.to != feeder || !pulse.high {
+                return;
+            }
+
+            let index = counters.iter().position(|&c| c == pulse.from).unwrap();
+
+            if fired[index].last() != Some(&presses) {
+                fired[index].push(presses);
+            }
+        });
+
+        if fired.iter().all(|presses| presses.len() >= 2) {
+            let periods: Option<Vec<_>> = fired
+                .iter()
+                .map(|presses| (presses[1] == 2 * presses[0]).then_some(presses[0]))
+                .collect();
+
+            return lcm_all(periods?);
+        }
+    }
+
+    None
+}
+
+/* == Input parsing == */
+
+impl<'a> Network<'a> {
+    /// Parses lines such as `%a -> inv, con`. Modules that are only used as
+    /// destinations are untyped.
+    fn parse_str(input: &'a str) -> ParseResult<Self> {
+        let mut names = AtomTable::default();
+        let mut modules = AtomVec::new();
+
+        let button = names.create(BUTTON);
+        let broadcaster = names.create(BROADCASTER);
+
+        for line in lines(input).filter(|line| !line.is_empty()) {
+            let (name, outputs) = line.key_values(" -> ")?;
+
+            let (kind, name) = match name.byte(0)? {
+                b'%' => (Kind::FlipFlop, name.slice(1, name.len())),
+                b'&' => (Kind::Conjunction, name.slice(1, name.len())),
+                _ if name.as_str() == BROADCASTER => (Kind::Broadcaster, name),
+                _ => return Err(name.expected("`%`, `&` or `broadcaster`")),
+            };
+
+            let module = Module {
+                kind,
+                outputs: outputs
+                    .map(|output| names.create(output.as_str()))
+                    .collect(),
+                inputs: Vec::new(),
+            };
+
+            modules.insert(names.create(name.as_str()), module);
+        }
+
+        // Makes room for the button and the modules only seen as destinations
+        while modules.len() < names.len() {
+            modules.insert(Atom::from_index(modules.len()), Module::default());
+        }
+
+        let links: Vec<_> = modules
+            .iter()
+            .flat_map(|(from, module)| module.outputs.iter().map(move |&to| (from, to)))
+            .collect();
+
+        for (from, to) in links {
+            modules[to].inputs.push(from);
+        }
+
+        Ok(Network {
+            names,
+            modules,
+            button,
+            broadcaster,
+        })
+    }
+}
+
+/* == Implementations == */
+
+impl<'n, 'a> Simulator<'n, 'a> {
+    /// Creates a simulator with every flip-flop off, and every conjunction
+    /// remembering low pulses.
+    fn new(network: &'n Network<'a>) -> Self {
+        let memory = network
+            .modules
+            .values()
+            .iter()
+            .map(|module| vec![false; module.inputs.len()])
+            .collect::<Vec<_>>();
+
+        Simulator {
+            network,
+            flip_flops: AtomVec::filled(network.modules.len(), false),
+            memory: memory.into(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Pushes the button, and processes pulses in the order they are sent
+    /// until there are none left. Every pulse is passed to `observe`, which
+    /// can count them, or trace them by printing their names.
+    fn press(&mut self, mut observe: impl FnMut(&Pulse)) {
+        self.queue.push_back(Pulse {
+            from: self.network.button,
+            to: self.network.broadcaster,
+            high: false,
+        });
+
+        while let Some(pulse) = self.queue.pop_front() {
+            observe(&pulse);
+
+            let module = &self.network.modules[pulse.to];
+
+            let high = match module.kind {
+                Kind::Untyped => continue,
+                Kind::Broadcaster => pulse.high,
+                Kind::FlipFlop if pulse.high => continue,
+                Kind::FlipFlop => {
+                    let on = &mut self.flip_flops[pulse.to];
+                    *on = !*on;
+                    *on
+                }
+                Kind::Conjunction => {
+                    let memory = &mut self.memory[pulse.to];
+                    let index = module.inputs.iter().position(|&m| m == pulse.from);
+                    memory[index.unwrap()] = pulse.high;
+                    !memory.iter().all(|&high| high)
+                }
+            };
+
+            self.queue.extend(module.outputs.iter().map(|&to| Pulse {
+                from: pulse.to,
+                to,
+                high,
+            }));
+        }
+    }
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+    use std::fmt::Display;
+
+    /// A pulse along with the names of its modules, for tracing.
+    ///
+    /// # Display
+    /// Like in the puzzle statement, e.g. `broadcaster -low-> a`.
+    struct NamedPulse<'a> {
+        from: &'a str,
+        to: &'a str,
+        high: bool,
+    }
+
+    impl<'a> Network<'a> {
+        fn named(&self, pulse: &Pulse) -> NamedPulse<'a> {
+            NamedPulse {
+                from: self.names.resolve(pulse.from),
+                to: self.names.resolve(pulse.to),
+                high: pulse.high,
+            }
+        }
+    }
+
+    impl Display for NamedPulse<'_> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let level = if self.high { "high" } else { "low" };
+            write!(f, "{} -{level}-> {}", self.from, self.to)
+        }
+    }
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&read_example_part(DAY, 1));
+        assert_eq!(result, Some(32000000));
+
+        let result = part_one(&read_example_part(DAY, 2));
+        assert_eq!(result, Some(11687500));
+    }
+
+    #[test]
+    fn test_part_two_without_machine() {
+        let result = part_two(&read_example_part(DAY, 2));
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_trace() {
+        let input = read_example_part(DAY, 2);
+        let network = Network::parse_str(&input).unwrap();
+        let mut simulator = Simulator::new(&network);
+
+        let trace = |simulator: &mut Simulator| {
+            let mut pulses = Vec::new();
+            simulator.press(|pulse| pulses.push(network.named(pulse).to_string()));
+            pulses
+        };
+
+        assert_eq!(
+            trace(&mut simulator),
+            [
+                "button -low-> broadcaster",
+                "broadcaster -low-> a",
+                "a -high-> inv",
+                "a -high-> con",
+                "inv -low-> b",
+                "con -high-> output",
+                "b -high-> con",
+                "con -low-> output",
+            ]
+        );
+
+        assert_eq!(trace(&mut simulator).len(), 6);
+        assert_eq!(trace(&mut simulator).last().unwrap(), "con -high-> output");
+    }
+}
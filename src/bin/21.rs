@@ -0,0 +1,244 @@
+advent_of_code::solution!(21);
+
+use std::{iter, mem};
+
+use advent_of_code::{
+    tools::{
+        math::{extrapolate, lcm},
+        Coords, Grid, UCoords, Vector,
+    },
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
+
+/* == Definitions == */
+
+const START: u8 = b'S';
+const ROCK: u8 = b'#';
+const STEPS: usize = 64;
+const TILED_STEPS: usize = 26_501_365;
+
+/// The number of consecutive samples that must grow quadratically before the
+/// growth is extrapolated, which is two zero third differences.
+const STABLE_SAMPLES: usize = 5;
+
+/// Gives up if the growth is still not quadratic after this many samples.
+const MAX_SAMPLES: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bounds {
+    Finite,
+    /// The garden repeats infinitely in every direction.
+    Tiled,
+}
+
+struct Garden {
+    tiles: Grid<u8>,
+    start: UCoords,
+}
+
+/* == Solutions == */
+
+pub fn part_one(input: &str) -> Option<u64> {
+    solve(input, STEPS, Bounds::Finite).report()?
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    solve(input, TILED_STEPS, Bounds::Tiled).report()?
+}
+
+/* == Input parsing == */
+
+impl Garden {
+    fn parse_input(input: &str) -> ParseResult<Garden> {
+        let tiles = Grid::parse_with(input, "`.`, `#` or `S`", |b| {
+            matches!(b, b'.' | ROCK | START).then_some(b)
+        })?;
+
+        let start = tiles.position(|&c| c == START).ok_or_else(|| {
+            let kind = ParseErrorKind::Expected("a starting tile `S`");
+            ParseError::new(1, 1, kind)
+        })?;
+
+        Ok(Garden { tiles, start })
+    }
+}
+
+/* == Functions == */
+
+fn solve(input: &str, steps: usize, bounds: Bounds) -> ParseResult<Option<u64>> {
+    let garden = Garden::parse_input(input)?;
+
+    Ok(match bounds {
+        Bounds::Finite => Some(garden.reachable(steps)),
+        Bounds::Tiled => garden.reachable_tiled(steps),
+    })
+}
+
+/* == Implementations == */
+
+impl Garden {
+    fn is_plot(&self, at: Coords, bounds: Bounds) -> bool {
+        let tile = match bounds {
+            Bounds::Finite => self.tiles.get(at),
+            Bounds::Tiled => {
+                let size = Coords::from(self.tiles.size());
+                let wrapped = Coords::new(at.x.rem_euclid(size.x), at.y.rem_euclid(size.y));
+                self.tiles.get(wrapped)
+            }
+        };
+
+        tile.is_some_and(|&tile| tile != ROCK)
+    }
+
+    /// Iterates over the number of plots first reached after each number of
+    /// steps, up to `radius` steps, with a breadth-first search. A plot that
+    /// can be reached in `n` steps can also be reached in `n + 2` by stepping
+    /// back and forth, so those reached in exactly `n` steps are the ones at a
+    /// distance of at most `n` with the same parity.
+    fn layers(&self, bounds: Bounds, radius: usize) -> impl Iterator<Item = u64> + '_ {
+        let start = Coords::from(self.start);
+
+        // Only the plots within the radius can be visited, from the top left
+        // corner of their bounding box
+        let (origin, size) = match bounds {
+            Bounds::Finite => (Coords::default(), self.tiles.size()),
+            Bounds::Tiled => {
+                let side = 2 * radius + 1;
+                (
+                    start - Coords::new(radius as i64, radius as i64),
+                    UCoords::new(side, side),
+                )
+            }
+        };
+
+        let mut visited = Grid::filled(size, false);
+        visited[UCoords::try_from(start - origin).unwrap()] = true;
+        let mut frontier = vec![start];
+
+        iter::from_fn(move || {
+            if frontier.is_empty() {
+                return None;
+            }
+
+            let layer = frontier.len() as u64;
+
+            for at in mem::take(&mut frontier) {
+                for next in at.neighbours() {
+                    if !self.is_plot(next, bounds) {
+                        continue;
+                    }
+
+                    if let Some(seen @ false) = visited.get_mut(next - origin) {
+                        *seen = true;
+                        frontier.push(next);
+                    }
+                }
+            }
+
+            Some(layer)
+        })
+        .take(radius + 1)
+    }
+
+    fn reachable(&self, steps: usize) -> u64 {
+        self.layers(Bounds::Finite, steps)
+            .skip(steps % 2)
+            .step_by(2)
+            .sum()
+    }
+
+    /// Once the search has crossed a few copies of the garden, its frontier
+    /// moves through the copies in a regular pattern. Every period of twice
+    /// the size of the garden, which preserves the parity of the steps, the
+    /// frontier gains a constant number of copies, so that the number of plots
+    /// reached grows quadratically.
+    ///
+    /// The counts are sampled every period until they are quadratic, which is
+    /// when their third differences are zero, and then extrapolated with
+    /// finite differences like the sequences of Day 9.
+    fn reachable_tiled(&self, steps: usize) -> Option<u64> {
+        let size = self.tiles.size();
+        let period = 2 * lcm(size.x as u64, size.y as u64)? as usize;
+
+        let radius = steps.min(steps % period + period * MAX_SAMPLES);
+
+        let mut totals = [0; 2];
+        let mut samples = Vec::new();
+        let mut searched = 0;
+
+        for (distance, layer) in self.layers(Bounds::Tiled, radius).enumerate() {
+            totals[distance % 2] += layer;
+            searched = distance;
+
+            if distance == steps {
+                return Some(totals[steps % 2]);
+            }
+
+            if distance % period != steps % period {
+                continue;
+            }
+
+            samples.push(totals[steps % 2] as i64);
+
+            if samples.len() >= STABLE_SAMPLES && is_quadratic(&samples) {
+                let periods = (steps - distance) / period;
+                let last = &samples[samples.len() - 3..];
+                return extrapolate(last, periods as i64 + 2).map(|n| n as u64);
+            }
+        }
+
+        // If the search ended early, the start is enclosed and every reachable
+        // plot has been counted
+        (searched < radius).then_some(totals[steps % 2])
+    }
+}
+
+/// Whether the last samples all lie on a quadratic.
+fn is_quadratic(samples: &[i64]) -> bool {
+    let mut differences = samples[samples.len() - STABLE_SAMPLES..].to_vec();
+
+    for _ in 0..3 {
+        differences = differences.windows(2).map(|w| w[1] - w[0]).collect();
+    }
+
+    differences.iter().all(|&d| d == 0)
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = &read_example(DAY);
+        assert_eq!(solve(input, 6, Bounds::Finite), Ok(Some(16)));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = &read_example(DAY);
+        let expected = [
+            (6, 16),
+            (10, 50),
+            (50, 1594),
+            (100, 6536),
+            (500, 167004),
+            (1000, 668697),
+            (5000, 16733044),
+        ];
+
+        for (steps, plots) in expected {
+            assert_eq!(solve(input, steps, Bounds::Tiled), Ok(Some(plots)));
+        }
+    }
+
+    #[test]
+    fn test_enclosed() {
+        let input = "#####\n#S.##\n#####";
+        assert_eq!(solve(input, 100, Bounds::Tiled), Ok(Some(1)));
+        assert_eq!(solve(input, 101, Bounds::Tiled), Ok(Some(1)));
+    }
+}
@@ -0,0 +1,219 @@
+advent_of_code::solution!(22);
+
+use advent_of_code::{
+    tools::{
+        parse::{lines, Span},
+        BoundingBox, Coords3, Grid, UCoords,
+    },
+    ParseResult, Report,
+};
+
+/* == Definitions == */
+
+/// A straight line of cubes, as the box between its two ends.
+type Brick = BoundingBox<Coords3>;
+
+/// The bricks once they have settled, from bottom to top, with the bricks
+/// that each one rests on. Bricks resting on the ground have no supporters.
+struct Stack {
+    supporters: Vec<Vec<usize>>,
+}
+
+/// The dominator tree of the bricks, rooted at the ground. A brick dominates
+/// another if every chain of supports from the ground to the other goes
+/// through it, so that the other falls when it is removed.
+struct Dominators {
+    /// The closest strict dominator of each brick, or [`None`] for the ground.
+    parents: Vec<Option<usize>>,
+    /// The depth of each brick in the tree, where the ground is at depth 0.
+    depths: Vec<usize>,
+}
+
+/* == Solutions == */
+
+/// A brick can be removed safely unless it is the only supporter of another.
+pub fn part_one(input: &str) -> Option<usize> {
+    let stack = Stack::settle(parse_input(input).report()?);
+    let mut essential = vec![false; stack.len()];
+
+    for supporters in &stack.supporters {
+        if let [single] = supporters[..] {
+            essential[single] = true;
+        }
+    }
+
+    Some(essential.iter().filter(|&&essential| !essential).count())
+}
+
+/// Removing a brick makes exactly the bricks that it dominates fall, so each
+/// brick falls once for each of its strict dominators other than the ground.
+/// The total is the sum of the depths in the dominator tree, minus one per
+/// brick for the ground.
+pub fn part_two(input: &str) -> Option<usize> {
+    let stack = Stack::settle(parse_input(input).report()?);
+    let dominators = stack.dominators();
+
+    Some(dominators.depths.iter().map(|depth| depth - 1).sum())
+}
+
+/* == Input parsing == */
+
+fn parse_input(input: &str) -> ParseResult<Vec<Brick>> {
+    lines(input).map(parse_brick).collect()
+}
+
+/// Parses a brick such as `1,0,1~1,2,1`.
+fn parse_brick(line: Span) -> ParseResult<Brick> {
+    let [x1, y1, z1, x2, y2, z2] = line.scan("{},{},{}~{},{},{}")?;
+
+    let ends = [(x1, y1, z1), (x2, y2, z2)]
+        .map(|(x, y, z)| Ok(Coords3::new(x.parse()?, y.parse()?, z.parse()?)));
+
+    let [start, end] = ends;
+    let brick = BoundingBox::point(start?).including(end?);
+
+    if brick.min.x < 0 || brick.min.y < 0 || brick.min.z < 1 {
+        return Err(line.expected("a brick above the ground"));
+    }
+
+    Ok(brick)
+}
+
+/* == Implementations == */
+
+impl Stack {
+    /// Lets the bricks fall from the lowest, keeping a height map of the top
+    /// of each column along with the brick that it belongs to. A brick falls
+    /// until it hits the highest column below it, and rests on every brick
+    /// whose top is at that height.
+    fn settle(mut bricks: Vec<Brick>) -> Stack {
+        bricks.sort_by_key(|brick| brick.min.z);
+
+        let size = bricks.iter().fold(UCoords::new(0, 0), |size, brick| {
+            UCoords::new(
+                size.x.max(brick.max.x as usize + 1),
+                size.y.max(brick.max.y as usize + 1),
+            )
+        });
+
+        let mut heights: Grid<(i64, Option<usize>)> = Grid::filled(size, (0, None));
+        let mut supporters = Vec::with_capacity(bricks.len());
+
+        for (index, brick) in bricks.iter().enumerate() {
+            let top = footprint(brick).map(|at| heights[at].0).max().unwrap();
+
+            let mut below: Vec<usize> = footprint(brick)
+                .filter(|&at| heights[at].0 == top)
+                .filter_map(|at| heights[at].1)
+                .collect();
+
+            below.sort_unstable();
+            below.dedup();
+            supporters.push(below);
+
+            let height = brick.max.z - brick.min.z + 1;
+
+            for at in footprint(brick) {
+                heights[at] = (top + height, Some(index));
+            }
+        }
+
+        Stack { supporters }
+    }
+
+    fn len(&self) -> usize {
+        self.supporters.len()
+    }
+
+    /// Builds the dominator tree from the bottom up. Supporters are always
+    /// settled before the bricks they support, so the closest dominator of a
+    /// brick is the closest common dominator of its supporters, which are
+    /// already in the tree.
+    fn dominators(&self) -> Dominators {
+        let mut dominators = Dominators {
+            parents: Vec::with_capacity(self.len()),
+            depths: Vec::with_capacity(self.len()),
+        };
+
+        for supporters in &self.supporters {
+            let parent = supporters
+                .iter()
+                .map(|&brick| Some(brick))
+                .reduce(|a, b| dominators.common(a, b))
+                .flatten();
+
+            dominators.depths.push(dominators.depth(parent) + 1);
+            dominators.parents.push(parent);
+        }
+
+        dominators
+    }
+}
+
+impl Dominators {
+    fn depth(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |brick| self.depths[brick])
+    }
+
+    /// Returns the deepest common ancestor of two nodes, by climbing from the
+    /// deepest one until they meet.
+    fn common(&self, mut a: Option<usize>, mut b: Option<usize>) -> Option<usize> {
+        while a != b {
+            if self.depth(a) < self.depth(b) {
+                (a, b) = (b, a);
+            }
+
+            a = self.parents[a.unwrap()];
+        }
+
+        a
+    }
+}
+
+/* == Functions == */
+
+/// Iterates over the columns that a brick occupies.
+fn footprint(brick: &Brick) -> impl Iterator<Item = UCoords> + '_ {
+    (brick.min.y..=brick.max.y).flat_map(move |y| {
+        (brick.min.x..=brick.max.x).map(move |x| UCoords::new(x as usize, y as usize))
+    })
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&read_example(DAY));
+        assert_eq!(result, Some(5));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&read_example(DAY));
+        assert_eq!(result, Some(7));
+    }
+
+    #[test]
+    fn test_supports() {
+        let stack = Stack::settle(parse_input(&read_example(DAY)).unwrap());
+
+        // Brick A supports B and C, which both support D and E, and so on
+        assert_eq!(
+            stack.supporters,
+            [
+                vec![],
+                vec![0],
+                vec![0],
+                vec![1, 2],
+                vec![1, 2],
+                vec![3, 4],
+                vec![5]
+            ]
+        );
+    }
+}
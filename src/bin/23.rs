@@ -0,0 +1,170 @@
+advent_of_code::solution!(23);
+
+use advent_of_code::{
+    tools::{maze::JunctionGraph, Direction, Grid, UCoords},
+    ParseError, ParseErrorKind, ParseResult, Report,
+};
+
+/* == Definitions == */
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Path,
+    Forest,
+    /// A steep slope, which can only be walked down in its direction.
+    Slope(Direction),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Slopes {
+    Slippery,
+    /// The slopes are walked like normal paths.
+    Climbable,
+}
+
+struct Trails {
+    tiles: Grid<Tile>,
+    start: UCoords,
+    end: UCoords,
+}
+
+/* == Solutions == */
+
+/// The slopes leading out of every junction make the maze a directed acyclic
+/// graph, where the longest path is found in linear time by relaxing the
+/// edges in topological order.
+pub fn part_one(input: &str) -> Option<u32> {
+    let trails = Trails::parse_input(input).report()?;
+    let (graph, start, end) = trails.junctions(Slopes::Slippery);
+
+    longest_path_acyclic(&graph, start, end)
+}
+
+/// Without slopes, the longest path has to be searched exhaustively, which is
+/// only tractable on the graph of junctions.
+pub fn part_two(input: &str) -> Option<u32> {
+    let trails = Trails::parse_input(input).report()?;
+    let (graph, start, end) = trails.junctions(Slopes::Climbable);
+
+    graph.longest_path(start, end)
+}
+
+/* == Input parsing == */
+
+impl Trails {
+    /// Parses the map, where the start and the end are the only paths on the
+    /// first and last rows.
+    fn parse_input(input: &str) -> ParseResult<Trails> {
+        let tiles = Grid::parse_with(input, "`.`, `#` or a slope", |b| Tile::try_from(b).ok())?;
+
+        let find_path = |y: usize| {
+            let x = tiles.row(y).iter().position(|&tile| tile == Tile::Path);
+            let kind = ParseErrorKind::Expected("a path on the first and last rows");
+            x.map(|x| UCoords::new(x, y))
+                .ok_or(ParseError::new(y + 1, 1, kind))
+        };
+
+        let start = find_path(0)?;
+        let end = find_path(tiles.height() - 1)?;
+
+        Ok(Trails { tiles, start, end })
+    }
+}
+
+/* == Functions == */
+
+/// Returns the longest path in a graph without cycles, or [`None`] if the end
+/// can't be reached or if there is a cycle.
+fn longest_path_acyclic(graph: &JunctionGraph, start: usize, end: usize) -> Option<u32> {
+    let mut incoming = vec![0; graph.len()];
+
+    for &(next, _) in (0..graph.len()).flat_map(|index| graph.edges(index)) {
+        incoming[next] += 1;
+    }
+
+    // Kahn's algorithm, which relaxes the edges of each junction once all of
+    // the paths leading to it are known
+    let mut ready: Vec<_> = (0..graph.len()).filter(|&i| incoming[i] == 0).collect();
+    let mut longest: Vec<Option<u32>> = vec![None; graph.len()];
+    let mut processed = 0;
+    longest[start] = Some(0);
+
+    while let Some(junction) = ready.pop() {
+        processed += 1;
+
+        for &(next, length) in graph.edges(junction) {
+            if let Some(distance) = longest[junction] {
+                longest[next] = longest[next].max(Some(distance + length));
+            }
+
+            incoming[next] -= 1;
+
+            if incoming[next] == 0 {
+                ready.push(next);
+            }
+        }
+    }
+
+    match processed == graph.len() {
+        true => longest[end],
+        false => None,
+    }
+}
+
+/* == Implementations == */
+
+impl Trails {
+    /// Compresses the map into the junction graph, also returning the
+    /// junctions of the start and the end.
+    fn junctions(&self, slopes: Slopes) -> (JunctionGraph, usize, usize) {
+        let graph =
+            JunctionGraph::compress(&self.tiles, [self.start, self.end], |at, direction| {
+                let next = self.tiles.step(at, direction).unwrap();
+
+                match (self.tiles[at], self.tiles[next]) {
+                    (Tile::Forest, _) | (_, Tile::Forest) => false,
+                    (Tile::Slope(slope), _) if slopes == Slopes::Slippery => slope == direction,
+                    _ => true,
+                }
+            });
+
+        let start = graph.index(self.start).unwrap();
+        let end = graph.index(self.end).unwrap();
+        (graph, start, end)
+    }
+}
+
+/* == Trait implementations == */
+
+impl TryFrom<u8> for Tile {
+    type Error = ();
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte {
+            b'.' => Ok(Tile::Path),
+            b'#' => Ok(Tile::Forest),
+            b'^' | b'>' | b'v' | b'<' => Direction::try_from(byte).map(Tile::Slope),
+            _ => Err(()),
+        }
+    }
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&read_example(DAY));
+        assert_eq!(result, Some(94));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&read_example(DAY));
+        assert_eq!(result, Some(154));
+    }
+}
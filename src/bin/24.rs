@@ -0,0 +1,239 @@
+advent_of_code::solution!(24);
+
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+use advent_of_code::{
+    tools::{
+        geometry::{cross, Embed, Line, LineIntersection},
+        math::gcd_all,
+        parse::{lines, Span},
+        rational::Rational,
+        Coords, Coords3,
+    },
+    ParseResult, Report,
+};
+
+/* == Definitions == */
+
+const TEST_AREA: RangeInclusive<i64> = 200_000_000_000_000..=400_000_000_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Hailstone {
+    position: Coords3,
+    velocity: Coords3,
+}
+
+/// Vectors widened to [`i128`], where cross products of cross products of the
+/// input still fit.
+type Wide = [i128; 3];
+
+/* == Solutions == */
+
+pub fn part_one(input: &str) -> Option<usize> {
+    crossings(input, TEST_AREA).report()
+}
+
+/// The rock is found with exact integer arithmetic, see [`throw`].
+pub fn part_two(input: &str) -> Option<i64> {
+    let hailstones = parse_input(input).report()?;
+    let rock = throw(&hailstones)?;
+
+    Some(rock.position.x + rock.position.y + rock.position.z)
+}
+
+/* == Input parsing == */
+
+fn parse_input(input: &str) -> ParseResult<Vec<Hailstone>> {
+    lines(input).map(parse_hailstone).collect()
+}
+
+/// Parses a hailstone such as `19, 13, 30 @ -2,  1, -2`.
+fn parse_hailstone(line: Span) -> ParseResult<Hailstone> {
+    let (position, velocity) = line.split_once("@")?;
+
+    Ok(Hailstone {
+        position: parse_vector(position)?,
+        velocity: parse_vector(velocity)?,
+    })
+}
+
+fn parse_vector(span: Span) -> ParseResult<Coords3> {
+    let components: Vec<i64> = span
+        .split(",")
+        .map(|component| component.trim().parse())
+        .collect::<ParseResult<_>>()?;
+
+    match components[..] {
+        [x, y, z] => Ok(Coords3::new(x, y, z)),
+        _ => Err(span.trim().expected("three coordinates")),
+    }
+}
+
+/* == Functions == */
+
+/// Counts the pairs of hailstones whose paths cross inside the test area in
+/// the future, ignoring the z axis. Crossings are exact rationals, so the
+/// bounds of the area are checked without rounding.
+fn crossings(input: &str, area: RangeInclusive<i64>) -> ParseResult<usize> {
+    let paths: Vec<_> = parse_input(input)?
+        .iter()
+        .map(|hailstone| {
+            let (p, v) = (hailstone.position, hailstone.velocity);
+            Line::new(Coords::new(p.x, p.y), Coords::new(v.x, v.y))
+        })
+        .collect();
+
+    let area = Rational::from(*area.start())..=Rational::from(*area.end());
+
+    let count = paths
+        .iter()
+        .tuple_combinations()
+        .filter(|(a, b)| match a.intersection(b) {
+            LineIntersection::Crossing { t, s } => {
+                t >= Rational::ZERO
+                    && s >= Rational::ZERO
+                    && a.at(t).iter().all(|x| area.contains(x))
+            }
+            _ => false,
+        })
+        .count();
+
+    Ok(count)
+}
+
+/// Finds the rock that hits every hailstone, from the first three that aren't
+/// degenerate.
+///
+/// In the frame of the first hailstone, which then sits still at the origin,
+/// the rock moves on a line through the origin with a relative position `P`
+/// and velocity `V`. Hitting another hailstone `(p, v)` means that
+/// `(P - p) × (V - v) = 0`, and since `P × V = 0` this is the linear system
+/// `P × v + p × V = p × v`.
+///
+/// Dotting it with `v` eliminates `P` and leaves `V · (p × v) = 0`, so `V` is
+/// orthogonal to the plane of each hailstone's path and the origin. Two such
+/// planes give the direction `w` of `V` as the cross product of their normals.
+/// Writing `P = m w` and `V = k w` then reduces the system to two integer
+/// unknowns, solved with Cramer's rule.
+///
+/// Solving in the first hailstone's frame, and for the direction before the
+/// magnitudes, keeps every product within [`i128`], unlike a direct 6×6
+/// elimination on the raw coordinates.
+fn throw(hailstones: &[Hailstone]) -> Option<Hailstone> {
+    let (origin, others) = hailstones.split_first()?;
+
+    let relative: Vec<(Wide, Wide)> = others
+        .iter()
+        .map(|hailstone| {
+            (
+                (hailstone.position - origin.position).embed(),
+                (hailstone.velocity - origin.velocity).embed(),
+            )
+        })
+        .collect();
+
+    let normals = relative.iter().map(|&(p, v)| cross(p, v));
+    let direction = normals
+        .tuple_combinations()
+        .map(|(a, b)| cross(a, b))
+        .find(|&w| w != [0; 3])?;
+    let w = primitive(direction);
+
+    // Each hailstone gives three equations `a * m + b * k = c`
+    let equations: Vec<_> = relative
+        .iter()
+        .flat_map(|&(p, v)| {
+            let (a, b, c) = (cross(w, v), cross(p, w), cross(p, v));
+            (0..3).map(move |i| [a[i], b[i], c[i]])
+        })
+        .collect();
+
+    let (m, k) = equations
+        .iter()
+        .tuple_combinations()
+        .find_map(|(&first, &second)| cramer(first, second))?;
+
+    if !equations.iter().all(|&[a, b, c]| a * m + b * k == c) {
+        return None;
+    }
+
+    let offset = |scale: i128| {
+        let [x, y, z] = w.map(|component| i64::try_from(component * scale));
+        Some(Coords3::new(x.ok()?, y.ok()?, z.ok()?))
+    };
+
+    let rock = Hailstone {
+        position: origin.position + offset(m)?,
+        velocity: origin.velocity + offset(k)?,
+    };
+
+    // The rock must also hit the first hailstone, and any degenerate one
+    let hits = hailstones.iter().all(|hailstone| {
+        let p = (rock.position - hailstone.position).embed();
+        let v = (rock.velocity - hailstone.velocity).embed();
+        cross(p, v) == [0; 3]
+    });
+
+    hits.then_some(rock)
+}
+
+/// Solves two equations `a * x + b * y = c` with Cramer's rule, or returns
+/// [`None`] if they aren't independent or the solution isn't integer.
+fn cramer([a1, b1, c1]: [i128; 3], [a2, b2, c2]: [i128; 3]) -> Option<(i128, i128)> {
+    let determinant = a1 * b2 - a2 * b1;
+
+    if determinant == 0 {
+        return None;
+    }
+
+    let x = c1 * b2 - c2 * b1;
+    let y = a1 * c2 - a2 * c1;
+
+    match x % determinant == 0 && y % determinant == 0 {
+        true => Some((x / determinant, y / determinant)),
+        false => None,
+    }
+}
+
+/// Divides a non-zero vector by the gcd of its components.
+fn primitive(vector: Wide) -> Wide {
+    let divisor = gcd_all(vector.map(i128::unsigned_abs));
+    let divisor = i128::try_from(divisor).expect("vector component out of range");
+
+    vector.map(|component| component / divisor)
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = crossings(&read_example(DAY), 7..=27);
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&read_example(DAY));
+        assert_eq!(result, Some(47));
+    }
+
+    #[test]
+    fn test_throw() {
+        let hailstones = parse_input(&read_example(DAY)).unwrap();
+
+        assert_eq!(
+            throw(&hailstones),
+            Some(Hailstone {
+                position: Coords3::new(24, 13, 10),
+                velocity: Coords3::new(-3, 1, 2),
+            })
+        );
+    }
+}
@@ -0,0 +1,84 @@
+advent_of_code::solution!(25);
+
+use advent_of_code::{
+    tools::graph::{Cut, Graph, GraphBuilder},
+    ParseResult, Report,
+};
+
+/* == Definitions == */
+
+/// The number of wires to disconnect.
+const WIRES: usize = 3;
+
+/* == Solutions == */
+
+pub fn part_one(input: &str) -> Option<usize> {
+    let graph = parse_input(input).report()?;
+    let cut = split(&graph)?;
+
+    Some(cut.side.len() * (graph.len() - cut.side.len()))
+}
+
+pub fn part_two(_input: &str) -> Option<u32> {
+    None
+}
+
+/* == Input parsing == */
+
+/// Parses lines such as `jqt: rhn xhk nvd`, where the connections go both
+/// ways.
+fn parse_input(input: &str) -> ParseResult<Graph> {
+    Ok(GraphBuilder::undirected().lines(input, ": ")?.build())
+}
+
+/* == Functions == */
+
+/// Finds the cut of [`WIRES`] edges that splits the components in two.
+///
+/// The minimum cut between two nodes on opposite sides is the global one, so
+/// one node is cut from each other node in turn with a maximum flow, which
+/// is deterministic and only needs a few augmenting paths per attempt.
+/// About half of the nodes are on the other side, so this stops quickly.
+///
+/// Nodes with only [`WIRES`] connections can be cut off on their own, which
+/// doesn't split anything, so cuts with a single node on a side are skipped.
+/// The source is the most connected node so that it is never cut off itself.
+fn split(graph: &Graph) -> Option<Cut> {
+    let source = graph
+        .nodes()
+        .max_by_key(|&node| graph.neighbours(node).len())?;
+
+    let sinks = graph.nodes().filter(|&node| node != source);
+
+    sinks.map(|sink| graph.min_cut(source, sink)).find(|cut| {
+        let sides = [cut.side.len(), graph.len() - cut.side.len()];
+        cut.weight <= WIRES && sides.iter().all(|&size| size > 1)
+    })
+}
+
+/* == Tests == */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code::template::*;
+
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&read_example(DAY));
+        assert_eq!(result, Some(54));
+    }
+
+    #[test]
+    fn test_split() {
+        let input = read_example(DAY);
+        let graph = parse_input(&input).unwrap();
+        let cut = split(&graph).unwrap();
+
+        assert_eq!(cut.weight, 3);
+        assert_eq!(
+            Some(cut.weight),
+            graph.global_min_cut().map(|cut| cut.weight)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::error::Error;
+use std::fmt::Display;
+
+/// The result of parsing puzzle input.
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// An error raised when the puzzle input does not have the expected format.
+///
+/// Lines and columns are 1-based and count bytes, which is fine for the ASCII
+/// inputs of Advent of Code. The offending line is kept to render a diagnostic.
+///
+/// # Display
+/// ```text
+/// line 3, column 6: expected `:`
+///   Game 3 8 green, 6 blue
+///        ^
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    kind: ParseErrorKind,
+    context: Option<String>,
+}
+
+/// The reason why parsing failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The input ended before the parser was done.
+    UnexpectedEnd,
+    /// Some token was expected, described in words (e.g. "`:`" or "a digit").
+    Expected(&'static str),
+    /// The value could not be interpreted as the named type.
+    Invalid(&'static str),
+}
+
+impl ParseError {
+    /// Creates an error at the given position, without any context.
+    pub fn new(line: usize, column: usize, kind: ParseErrorKind) -> Self {
+        Self {
+            line,
+            column,
+            kind,
+            context: None,
+        }
+    }
+
+    /// Creates an error for input that ended after `lines` lines.
+    pub fn unexpected_end(lines: usize) -> Self {
+        Self::new(lines + 1, 1, ParseErrorKind::UnexpectedEnd)
+    }
+
+    /// Attaches the text of the offending line, shown below the message.
+    pub fn with_context(mut self, line: &str) -> Self {
+        self.context = Some(line.to_owned());
+        self
+    }
+
+    /// Returns the 1-based line number.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the 1-based byte column.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns the reason why parsing failed.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )?;
+
+        if let Some(context) = &self.context {
+            write!(f, "\n  {context}\n  {:>1$}", "^", self.column)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => f.write_str("unexpected end of input"),
+            Self::Expected(what) => write!(f, "expected {what}"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Converts a [`ParseResult`] into the [`Option`] returned by solutions.
+pub trait Report<T> {
+    /// Returns the value, or prints the diagnostic to stderr and returns [`None`],
+    /// which the runner displays as a failed part.
+    fn report(self) -> Option<T>;
+}
+
+impl<T> Report<T> for ParseResult<T> {
+    fn report(self) -> Option<T> {
+        self.map_err(|err| eprintln!("Parse error at {err}")).ok()
+    }
+}
@@ -1,7 +1,7 @@
-#![feature(map_try_insert)]
-
 mod day;
+mod error;
 pub mod template;
 pub mod tools;
 
 pub use day::*;
+pub use error::*;
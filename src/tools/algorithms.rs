@@ -1,37 +1,189 @@
+//! Measurements of simple polygons on the integer lattice, given by their
+//! vertices in order. The polygon is closed implicitly, so the first vertex
+//! should not be repeated at the end.
+//!
+//! Paths may contain every lattice point along their edges (such as a loop of
+//! pipes), or only their corners (such as dig instructions), as edges are
+//! measured between consecutive vertices.
+
 use std::iter;
 
 use itertools::Itertools;
 
-use super::Coords;
+use super::{math::gcd, Coords};
+
+/// Where a point lies relative to a polygon.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+/// Computes twice the signed area of a polygon with the shoelace formula,
+/// which is always an integer. It is positive if the vertices go clockwise
+/// with the y axis pointing down, such as on a grid.
+pub fn double_signed_area(path: impl IntoIterator<Item = Coords>) -> i64 {
+    edges(path).map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+}
 
 /// Computes the enclosed area of a polygon, given its vertices,
 /// using the shoelace formula (fast determinant-based version).
-pub fn enclosed_area(mut path: impl Iterator<Item = Coords>) -> u64 {
-    let first = path.next().unwrap_or_default();
+pub fn enclosed_area(path: impl IntoIterator<Item = Coords>) -> u64 {
+    double_signed_area(path).unsigned_abs() / 2
+}
+
+/// Computes the number of lattice points on the boundary of a polygon, which
+/// is also its length if all edges are horizontal, vertical or diagonal.
+pub fn perimeter(path: impl IntoIterator<Item = Coords>) -> u64 {
+    edges(path).map(|(a, b)| edge_steps(a, b)).sum()
+}
+
+/// Computes the number of lattice points strictly inside a polygon with Pick's
+/// theorem, `A = I + B / 2 - 1`. For a loop through the centres of grid cells,
+/// this is the number of cells enclosed by the loop.
+pub fn interior_points(path: impl IntoIterator<Item = Coords>) -> u64 {
+    let (double_area, boundary) = measure(path);
+    (double_area + 2).saturating_sub(boundary) / 2
+}
+
+/// Computes the number of lattice points inside or on the boundary of a polygon.
+/// For a trench dug through grid cells, this is the number of cells covered by
+/// the trench and its interior.
+pub fn covered_cells(path: impl IntoIterator<Item = Coords>) -> u64 {
+    let (double_area, boundary) = measure(path);
+    (double_area + boundary + 2) / 2
+}
 
-    let sum: i64 = iter::once(first)
+/// Finds whether a point is inside a polygon by casting a ray towards positive
+/// x and counting the edges that it crosses.
+pub fn locate_point(path: impl IntoIterator<Item = Coords>, point: Coords) -> Location {
+    let mut inside = false;
+
+    for (a, b) in edges(path) {
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let cross = dx * (point.y - a.y) - (point.x - a.x) * dy;
+
+        let within_x = a.x.min(b.x) <= point.x && point.x <= a.x.max(b.x);
+        let within_y = a.y.min(b.y) <= point.y && point.y <= a.y.max(b.y);
+
+        if cross == 0 && within_x && within_y {
+            return Location::Boundary;
+        }
+
+        // Half-open on y, so that a ray through a vertex is only counted once
+        if (a.y > point.y) != (b.y > point.y) && (cross > 0) == (dy > 0) {
+            inside = !inside;
+        }
+    }
+
+    match inside {
+        true => Location::Inside,
+        false => Location::Outside,
+    }
+}
+
+/// Returns the unsigned double area and the number of boundary points in one
+/// pass, as paths are often computed lazily.
+fn measure(path: impl IntoIterator<Item = Coords>) -> (u64, u64) {
+    let (area, boundary) = edges(path).fold((0_i64, 0), |(area, boundary), (a, b)| {
+        (area + a.x * b.y - b.x * a.y, boundary + edge_steps(a, b))
+    });
+
+    (area.unsigned_abs(), boundary)
+}
+
+/// Returns the number of lattice steps along an edge.
+fn edge_steps(a: Coords, b: Coords) -> u64 {
+    gcd((b.x - a.x).unsigned_abs(), (b.y - a.y).unsigned_abs())
+}
+
+/// Returns the edges of a polygon, closing it back to the first vertex.
+fn edges(path: impl IntoIterator<Item = Coords>) -> impl Iterator<Item = (Coords, Coords)> {
+    let mut path = path.into_iter();
+    let first = path.next();
+
+    first
+        .into_iter()
         .chain(path)
-        .chain(iter::once(first))
-        .map(Coords::from)
+        .chain(iter::once(first).flatten())
         .tuple_windows()
-        .map(|(a, b)| a.x * b.y - b.x * a.y)
-        .sum();
-
-    sum.unsigned_abs() / 2
 }
 
 #[cfg(test)]
 mod tests {
-    use super::enclosed_area;
-    use crate::tools::*;
+    use super::*;
+    use crate::tools::Direction;
+
+    fn polygon(vertices: &[(i64, i64)]) -> Vec<Coords> {
+        vertices.iter().map(|&(x, y)| Coords::new(x, y)).collect()
+    }
+
+    /// The corners of the trench in the Day 18 example.
+    fn trench() -> Vec<Coords> {
+        let instructions = "R6 D5 L2 D2 R2 D2 L5 U2 L1 U2 R2 U3 L2 U2";
+        let mut at = Coords::default();
+
+        instructions
+            .split(' ')
+            .map(|instruction| {
+                let (direction, length) = instruction.split_at(1);
+                let direction = Direction::try_from(direction.as_bytes()[0]).unwrap();
+                at += direction * length.parse::<i64>().unwrap();
+                at
+            })
+            .collect()
+    }
+
+    /// The corners of the loop in the second Day 10 example.
+    fn pipes() -> Vec<Coords> {
+        let corners = "1,1 9,1 9,7 6,7 6,5 8,5 8,2 2,2 2,5 4,5 4,7 1,7";
+
+        corners
+            .split(' ')
+            .map(|corner| {
+                let (x, y) = corner.split_once(',').unwrap();
+                Coords::new(x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect()
+    }
 
     #[test]
     fn test_enclosed_area() {
-        let path = [(0, 0), (0, 2), (2, 2), (2, 0)]
-            .iter()
-            .map(|&(x, y)| Coords::new(x, y));
+        let square = polygon(&[(0, 0), (0, 2), (2, 2), (2, 0)]);
+
+        assert_eq!(enclosed_area(square.iter().copied()), 4);
+        assert_eq!(double_signed_area(square.iter().copied()), -8);
+        assert_eq!(double_signed_area(square.into_iter().rev()), 8);
+        assert_eq!(enclosed_area([]), 0);
+    }
+
+    #[test]
+    fn test_lattice_points() {
+        assert_eq!(perimeter(trench()), 38);
+        assert_eq!(covered_cells(trench()), 62);
+
+        assert_eq!(perimeter(pipes()), 46);
+        assert_eq!(interior_points(pipes()), 4);
+
+        // Diagonal edges only go through some lattice points
+        let triangle = polygon(&[(0, 0), (4, 0), (0, 6)]);
+        assert_eq!(perimeter(triangle.iter().copied()), 12);
+        assert_eq!(interior_points(triangle.iter().copied()), 7);
+        assert_eq!(covered_cells(triangle), 19);
+    }
+
+    #[test]
+    fn test_locate_point() {
+        let locate = |x, y| locate_point(pipes(), Coords::new(x, y));
 
-        let area = enclosed_area(path);
-        assert_eq!(area, 4);
+        assert_eq!(locate(2, 6), Location::Inside);
+        assert_eq!(locate(8, 6), Location::Inside);
+        assert_eq!(locate(5, 6), Location::Outside);
+        assert_eq!(locate(3, 3), Location::Outside);
+        assert_eq!(locate(0, 2), Location::Outside);
+        assert_eq!(locate(5, 1), Location::Boundary);
+        assert_eq!(locate(9, 7), Location::Boundary);
+        assert_eq!(locate(6, 6), Location::Boundary);
     }
 }
@@ -1,37 +1,316 @@
-use std::{collections::HashMap, hash::Hash};
+//! Interning of keys such as labels or names into small integers, which are
+//! cheaper to compare, hash and copy, and can index dense arrays.
+//!
+//! ```
+//! use advent_of_code::tools::atom::{AtomTable, AtomVec};
+//!
+//! let mut table = AtomTable::new();
+//! let a = table.create("aaa");
+//! let b = table.create("bbb");
+//!
+//! assert_eq!(table.create("aaa"), a);
+//! assert_eq!(table.get("bbb"), Some(b));
+//! assert_eq!(table.resolve(b), &"bbb");
+//!
+//! let mut values = AtomVec::filled(table.len(), 0);
+//! values[b] += 5;
+//! assert_eq!(values.get(b), Some(&5));
+//! ```
 
-pub type Atom = usize;
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
 
-pub struct AtomTable<T: Eq + Hash> {
-    counter: usize,
-    atoms: HashMap<T, usize>,
+/// A key interned in an [`AtomTable`], which is the index of the key in the
+/// table. The tag is a marker type that prevents mixing atoms of different
+/// tables, such as an uninhabited `enum Label {}`.
+pub struct Atom<Tag = ()> {
+    index: usize,
+    marker: PhantomData<fn() -> Tag>,
+}
+
+/// Interns keys, giving the same atom to equal keys. Atoms are numbered from 0
+/// in order of creation.
+pub struct AtomTable<T: Eq + Hash, Tag = ()> {
+    keys: Vec<T>,
+    atoms: HashMap<T, Atom<Tag>>,
+}
+
+/// A vector with a value for each atom of a table.
+pub struct AtomVec<V, Tag = ()> {
+    values: Vec<V>,
+    marker: PhantomData<fn() -> Tag>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<Tag> Atom<Tag> {
+    /// Creates the atom with the given index, which should come from the
+    /// table that the atom is used with.
+    pub fn from_index(index: usize) -> Self {
+        Self {
+            index,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self.index
+    }
 }
 
 impl<T: Eq + Hash> AtomTable<T> {
+    /// Creates an untagged table. Tagged tables are created with [`Default`].
     pub fn new() -> Self {
-        Self {
-            counter: 0,
-            atoms: HashMap::new(),
+        Self::default()
+    }
+}
+
+impl<T: Eq + Hash, Tag> AtomTable<T, Tag> {
+    /// Returns the atom of a key, creating it if the key is new.
+    pub fn create(&mut self, key: T) -> Atom<Tag>
+    where
+        T: Clone,
+    {
+        if let Some(&atom) = self.atoms.get(&key) {
+            return atom;
         }
+
+        // Keys are stored twice, so that atoms can be resolved by index
+        let atom = Atom::from_index(self.keys.len());
+        self.keys.push(key.clone());
+        self.atoms.insert(key, atom);
+        atom
+    }
+
+    /// Returns the atom of a key if it was created, looking it up by any
+    /// borrowed form of the key, such as `str` for `String` or `&str` keys.
+    pub fn get<Q>(&self, key: &Q) -> Option<Atom<Tag>>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.atoms.get(key).copied()
+    }
+
+    /// Returns the key of an atom.
+    ///
+    /// # Panics
+    /// If the atom was not created by this table.
+    pub fn resolve(&self, atom: Atom<Tag>) -> &T {
+        &self.keys[atom.index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
     }
 
-    pub fn create(&mut self, key: T) -> usize {
-        match self.atoms.try_insert(key, self.counter) {
-            Ok(atom) => {
-                self.counter += 1;
-                *atom
-            }
-            Err(occupied) => *occupied.entry.get(),
+    /// Iterates over the atoms and their keys, in order of creation.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Atom<Tag>, &T)> + '_ {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| (Atom::from_index(index), key))
+    }
+}
+
+impl<V, Tag> AtomVec<V, Tag> {
+    pub fn new() -> Self {
+        Self::from(Vec::new())
+    }
+
+    /// Creates a vector with the same value for `len` atoms, usually the
+    /// length of a table.
+    pub fn filled(len: usize, value: V) -> Self
+    where
+        V: Clone,
+    {
+        Self::from(vec![value; len])
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn get(&self, atom: Atom<Tag>) -> Option<&V> {
+        self.values.get(atom.index)
+    }
+
+    pub fn get_mut(&mut self, atom: Atom<Tag>) -> Option<&mut V> {
+        self.values.get_mut(atom.index)
+    }
+
+    /// Sets the value of an atom, growing the vector with default values if
+    /// the atom is past its end.
+    pub fn insert(&mut self, atom: Atom<Tag>, value: V)
+    where
+        V: Default,
+    {
+        if atom.index >= self.values.len() {
+            self.values.resize_with(atom.index + 1, V::default);
         }
+
+        self.values[atom.index] = value;
+    }
+
+    /// Iterates over the atoms and their values, in order of creation.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Atom<Tag>, &V)> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (Atom::from_index(index), value))
+    }
+
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+// Implemented by hand, as deriving would require the tag to implement them
+impl<Tag> Clone for Atom<Tag> {
+    fn clone(&self) -> Self {
+        *self
     }
+}
+
+impl<Tag> Copy for Atom<Tag> {}
+
+impl<Tag> PartialEq for Atom<Tag> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<Tag> Eq for Atom<Tag> {}
+
+impl<Tag> PartialOrd for Atom<Tag> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Tag> Ord for Atom<Tag> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+impl<Tag> Hash for Atom<Tag> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
 
-    pub fn get(&self, key: T) -> Option<usize> {
-        self.atoms.get(&key).copied()
+impl<Tag> Debug for Atom<Tag> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Atom({})", self.index)
     }
 }
 
-impl<T: Eq + Hash> Default for AtomTable<T> {
+impl<V: Clone, Tag> Clone for AtomVec<V, Tag> {
+    fn clone(&self) -> Self {
+        Self::from(self.values.clone())
+    }
+}
+
+impl<V: Debug, Tag> Debug for AtomVec<V, Tag> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.values.fmt(f)
+    }
+}
+
+impl<T: Eq + Hash, Tag> Default for AtomTable<T, Tag> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            atoms: HashMap::new(),
+        }
+    }
+}
+
+impl<V, Tag> Default for AtomVec<V, Tag> {
     fn default() -> Self {
         Self::new()
     }
 }
+
+impl<V, Tag> From<Vec<V>> for AtomVec<V, Tag> {
+    fn from(values: Vec<V>) -> Self {
+        Self {
+            values,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<V, Tag> Index<Atom<Tag>> for AtomVec<V, Tag> {
+    type Output = V;
+
+    fn index(&self, atom: Atom<Tag>) -> &Self::Output {
+        &self.values[atom.index]
+    }
+}
+
+impl<V, Tag> IndexMut<Atom<Tag>> for AtomVec<V, Tag> {
+    fn index_mut(&mut self, atom: Atom<Tag>) -> &mut Self::Output {
+        &mut self.values[atom.index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    enum Name {}
+
+    #[test]
+    fn test_table() {
+        let mut table: AtomTable<String, Name> = AtomTable::default();
+        let names = ["broadcaster", "a", "inv", "a"].map(String::from);
+        let atoms = names.map(|name| table.create(name));
+
+        assert_eq!(atoms[1], atoms[3]);
+        assert_eq!(atoms.map(Atom::index), [0, 1, 2, 1]);
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get("inv"), Some(atoms[2]));
+        assert_eq!(table.get("con"), None);
+        assert_eq!(table.resolve(atoms[0]), "broadcaster");
+
+        let keys: Vec<_> = table.iter().map(|(_, key)| key.as_str()).collect();
+        assert_eq!(keys, ["broadcaster", "a", "inv"]);
+    }
+
+    #[test]
+    fn test_vec() {
+        let mut values = AtomVec::<_, Name>::new();
+        let atom = Atom::from_index(2);
+
+        values.insert(atom, 'x');
+        assert_eq!(values.values(), ['\0', '\0', 'x']);
+        assert_eq!(values[atom], 'x');
+        assert_eq!(values.get(Atom::from_index(3)), None);
+
+        *values.get_mut(Atom::from_index(0)).unwrap() = 'y';
+        let first = values
+            .iter()
+            .next()
+            .map(|(atom, &value)| (atom.index(), value));
+        assert_eq!(first, Some((0, 'y')));
+    }
+}
@@ -0,0 +1,236 @@
+//! 3D counterparts of [`Coords`](super::Coords) and [`UCoords`](super::UCoords).
+//! Shared operations such as neighbours and bounding boxes are provided by the
+//! [`Vector`](super::Vector) trait.
+
+use std::{
+    fmt::Display,
+    num::TryFromIntError,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Coords3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UCoords3 {
+    pub x: usize,
+    pub y: usize,
+    pub z: usize,
+}
+
+impl Coords3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Coords3 {
+        Coords3 { x, y, z }
+    }
+
+    pub fn norm_l1(&self) -> u64 {
+        self.x.unsigned_abs() + self.y.unsigned_abs() + self.z.unsigned_abs()
+    }
+
+    /// Returns the cross product, which is orthogonal to both vectors.
+    pub fn cross(self, rhs: Coords3) -> Coords3 {
+        Coords3 {
+            x: self.y * rhs.z - self.z * rhs.y,
+            y: self.z * rhs.x - self.x * rhs.z,
+            z: self.x * rhs.y - self.y * rhs.x,
+        }
+    }
+}
+
+impl UCoords3 {
+    pub fn new(x: usize, y: usize, z: usize) -> UCoords3 {
+        UCoords3 { x, y, z }
+    }
+}
+
+impl Add for Coords3 {
+    type Output = Coords3;
+
+    fn add(self, rhs: Coords3) -> Self::Output {
+        Coords3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
+
+impl AddAssign for Coords3 {
+    fn add_assign(&mut self, rhs: Coords3) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Coords3 {
+    type Output = Coords3;
+
+    fn sub(self, rhs: Coords3) -> Self::Output {
+        Coords3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl SubAssign for Coords3 {
+    fn sub_assign(&mut self, rhs: Coords3) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Coords3 {
+    type Output = Coords3;
+
+    fn neg(self) -> Self::Output {
+        Coords3 {
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+}
+
+impl Mul<i64> for Coords3 {
+    type Output = Coords3;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Coords3 {
+            x: rhs * self.x,
+            y: rhs * self.y,
+            z: rhs * self.z,
+        }
+    }
+}
+
+/// Divides each component, rounding towards zero.
+impl Div<i64> for Coords3 {
+    type Output = Coords3;
+
+    fn div(self, rhs: i64) -> Self::Output {
+        Coords3 {
+            x: self.x / rhs,
+            y: self.y / rhs,
+            z: self.z / rhs,
+        }
+    }
+}
+
+impl Display for Coords3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+impl Display for UCoords3 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+impl From<(i64, i64, i64)> for Coords3 {
+    fn from(val: (i64, i64, i64)) -> Coords3 {
+        Coords3 {
+            x: val.0,
+            y: val.1,
+            z: val.2,
+        }
+    }
+}
+
+impl From<(usize, usize, usize)> for UCoords3 {
+    fn from(val: (usize, usize, usize)) -> UCoords3 {
+        UCoords3 {
+            x: val.0,
+            y: val.1,
+            z: val.2,
+        }
+    }
+}
+
+/// Fails if any component is negative.
+impl TryFrom<Coords3> for UCoords3 {
+    type Error = TryFromIntError;
+
+    fn try_from(val: Coords3) -> Result<UCoords3, Self::Error> {
+        Ok(UCoords3 {
+            x: val.x.try_into()?,
+            y: val.y.try_into()?,
+            z: val.z.try_into()?,
+        })
+    }
+}
+
+/// Fails if any component does not fit in an [`i64`].
+impl TryFrom<UCoords3> for Coords3 {
+    type Error = TryFromIntError;
+
+    fn try_from(val: UCoords3) -> Result<Coords3, Self::Error> {
+        Ok(Coords3 {
+            x: val.x.try_into()?,
+            y: val.y.try_into()?,
+            z: val.z.try_into()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{BoundingBox, Vector};
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Coords3::new(1, 2, 3);
+        let b = Coords3::new(-4, 5, 0);
+
+        assert_eq!(a + b, Coords3::new(-3, 7, 3));
+        assert_eq!(-a * 2, Coords3::new(-2, -4, -6));
+        assert_eq!(Coords3::new(7, -7, 8) / 2, Coords3::new(3, -3, 4));
+        assert_eq!(a.dot(b), 6);
+        assert_eq!(a.cross(b), Coords3::new(-15, -12, 13));
+        assert_eq!(a.cross(b).dot(a), 0);
+        assert_eq!(b.norm_linf(), 5);
+        assert_eq!(Vector::norm_l1(b), 9);
+    }
+
+    #[test]
+    fn test_neighbours_and_bounds() {
+        let origin = Coords3::default();
+        let neighbours: Vec<_> = origin.neighbours().collect();
+
+        assert_eq!(neighbours.len(), 6);
+        assert!(neighbours.iter().all(|n| n.norm_l1() == 1));
+
+        let points = [(1, 0, 2), (-1, 4, 2), (0, 1, 5)].map(Coords3::from);
+        let bounds = BoundingBox::from_points(points).unwrap();
+
+        assert_eq!(bounds.min, Coords3::new(-1, 0, 2));
+        assert_eq!(bounds.max, Coords3::new(1, 4, 5));
+        assert_eq!(bounds.size(), Coords3::new(3, 5, 4));
+        assert!(bounds.contains(Coords3::new(0, 4, 3)));
+        assert!(!bounds.contains(Coords3::new(0, 5, 3)));
+
+        let other = BoundingBox::point(Coords3::new(1, 4, 5)).including(Coords3::new(3, 9, 9));
+        assert_eq!(
+            bounds.intersection(&other),
+            Some(BoundingBox::point(bounds.max))
+        );
+        assert_eq!(
+            bounds.intersection(&BoundingBox::point(Coords3::new(2, 0, 2))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        let unsigned = UCoords3::try_from(Coords3::new(1, 2, 3));
+        assert_eq!(unsigned, Ok(UCoords3::new(1, 2, 3)));
+        assert!(UCoords3::try_from(Coords3::new(1, -2, 3)).is_err());
+        assert!(Coords3::try_from(UCoords3::new(usize::MAX, 0, 0)).is_err());
+    }
+}
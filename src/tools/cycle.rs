@@ -0,0 +1,208 @@
+//! Cycle detection for sequences `x0, f(x0), f(f(x0)), ...` produced by a
+//! deterministic step function, which must eventually repeat if the number of
+//! states is finite. This allows jumping to a step far in the future, such as
+//! the billionth one, without simulating every step.
+//!
+//! [`floyd`] and [`brent`] only keep a couple of states in memory, but have to
+//! replay the sequence to reach a step. [`detect`] remembers every state until
+//! the first repeat, so any step can then be looked up directly.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// A cycle in a sequence, where the state at step `i` is the same as the state
+/// at step `i + length` for all `i >= start`. These are often named `mu` and
+/// `lambda`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// The states of a sequence until its first repetition, with the cycle that
+/// they form.
+#[derive(Clone, Debug)]
+pub struct Periodic<S> {
+    states: Vec<S>,
+    cycle: Cycle,
+}
+
+/* -------------------------------------------------------------------------- */
+
+/// Finds the cycle with Floyd's tortoise and hare algorithm.
+pub fn floyd<S: Clone + PartialEq>(start: S, f: impl Fn(&S) -> S) -> Cycle {
+    // Find a meeting point within the cycle, at a multiple of its length
+    let mut tortoise = f(&start);
+    let mut hare = f(&tortoise);
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&f(&hare));
+    }
+
+    // Both are now `start` steps away from the first state of the cycle
+    let mut cycle_start = 0;
+    tortoise = start;
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        cycle_start += 1;
+    }
+
+    let mut length = 1;
+    hare = f(&tortoise);
+
+    while tortoise != hare {
+        hare = f(&hare);
+        length += 1;
+    }
+
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Finds the cycle with Brent's algorithm, which usually calls `f` fewer times
+/// than [`floyd`].
+pub fn brent<S: Clone + PartialEq>(start: S, f: impl Fn(&S) -> S) -> Cycle {
+    // Find the length by teleporting the tortoise at increasing powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = start.clone();
+    let mut hare = f(&start);
+
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+
+        hare = f(&hare);
+        length += 1;
+    }
+
+    // Walk two pointers that are `length` steps apart until they meet
+    let mut cycle_start = 0;
+    tortoise = start.clone();
+    hare = start;
+
+    for _ in 0..length {
+        hare = f(&hare);
+    }
+
+    while tortoise != hare {
+        tortoise = f(&tortoise);
+        hare = f(&hare);
+        cycle_start += 1;
+    }
+
+    Cycle {
+        start: cycle_start,
+        length,
+    }
+}
+
+/// Finds the cycle by remembering every state in a [`HashMap`], which calls `f`
+/// once per state before the first repeat.
+pub fn detect<S: Clone + Hash + Eq>(start: S, f: impl Fn(&S) -> S) -> Periodic<S> {
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = start;
+
+    loop {
+        if let Some(&cycle_start) = seen.get(&state) {
+            let cycle = Cycle {
+                start: cycle_start,
+                length: states.len() - cycle_start,
+            };
+
+            return Periodic { states, cycle };
+        }
+
+        let next = f(&state);
+        seen.insert(state.clone(), states.len());
+        states.push(state);
+        state = next;
+    }
+}
+
+/// Returns the state at step `n` of the sequence, using [`brent`] to skip all
+/// full turns of the cycle.
+pub fn nth<S: Clone + PartialEq>(start: S, f: impl Fn(&S) -> S, n: usize) -> S {
+    let steps = brent(start.clone(), &f).reduce(n);
+    (0..steps).fold(start, |state, _| f(&state))
+}
+
+impl Cycle {
+    /// Returns the first step that has the same state as step `n`.
+    pub fn reduce(&self, n: usize) -> usize {
+        match n < self.start {
+            true => n,
+            false => self.start + (n - self.start) % self.length,
+        }
+    }
+}
+
+impl<S> Periodic<S> {
+    pub fn cycle(&self) -> Cycle {
+        self.cycle
+    }
+
+    /// Returns the states from the start of the sequence to the end of the
+    /// first turn of the cycle.
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// Returns the state at step `n`.
+    pub fn nth(&self, n: usize) -> &S {
+        &self.states[self.cycle.reduce(n)]
+    }
+
+    /// Returns a value derived from the state at step `n`.
+    pub fn nth_value<V>(&self, n: usize, value: impl FnOnce(&S) -> V) -> V {
+        value(self.nth(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 3, 9, 27, 81, 43, 29, 87, 61, 83, 49, 47, 41, 23, 69, 7, 21, 63, 89, 67, 1, 3, ...
+    /// modulo 100, after a prefix of two states.
+    fn step(x: &u64) -> u64 {
+        match *x {
+            0 => 5,
+            5 => 3,
+            x => x * 3 % 100,
+        }
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        let cycle = |start, length| Cycle { start, length };
+
+        assert_eq!(floyd(0, step), cycle(2, 20));
+        assert_eq!(brent(0, step), cycle(2, 20));
+        assert_eq!(detect(0, step).cycle(), cycle(2, 20));
+        assert_eq!(brent(3, step), cycle(0, 20));
+        assert_eq!(floyd(7, |&x| x), cycle(0, 1));
+    }
+
+    #[test]
+    fn test_nth() {
+        let periodic = detect(0, step);
+        let slow = |n| (0..n).fold(0, |x, _| step(&x));
+
+        for n in [0, 1, 2, 21, 22, 23, 1_000] {
+            assert_eq!(*periodic.nth(n), slow(n));
+            assert_eq!(nth(0, step, n), slow(n));
+        }
+
+        assert_eq!(periodic.states().len(), 22);
+        assert_eq!(periodic.nth_value(1_000_000_000, |x| x % 10), 7);
+    }
+}
@@ -0,0 +1,347 @@
+use std::{
+    fmt::Display,
+    ops::{Mul, Neg},
+};
+
+use super::Coords;
+
+/// One of the four cardinal directions. The y axis points south, as in the
+/// puzzle grids, so that [`Direction::North`] is the unit vector `(0, -1)`.
+///
+/// # Display
+/// Directions are displayed as the arrows `^`, `>`, `v` and `<`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+/// One of the eight principal compass directions, clockwise from north.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+/// A change of direction, relative to the current one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Turn {
+    Straight,
+    Right,
+    Back,
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise from north, in the order of [`Direction::index`].
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Returns a compact index in `0..4`, to be used for lookup tables.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the direction with the given index, wrapping around.
+    pub const fn from_index(index: usize) -> Direction {
+        Self::ALL[index % 4]
+    }
+
+    /// Returns the unit vector pointing in this direction.
+    pub const fn coords(self) -> Coords {
+        match self {
+            Direction::North => Coords { x: 0, y: -1 },
+            Direction::East => Coords { x: 1, y: 0 },
+            Direction::South => Coords { x: 0, y: 1 },
+            Direction::West => Coords { x: -1, y: 0 },
+        }
+    }
+
+    pub const fn turn_left(self) -> Direction {
+        Self::from_index(self.index() + 3)
+    }
+
+    pub const fn turn_right(self) -> Direction {
+        Self::from_index(self.index() + 1)
+    }
+
+    pub const fn reverse(self) -> Direction {
+        Self::from_index(self.index() + 2)
+    }
+
+    pub const fn turn(self, turn: Turn) -> Direction {
+        Self::from_index(self.index() + turn as usize)
+    }
+
+    /// Returns the turn that needs to be taken to face the other direction.
+    pub const fn turn_to(self, other: Direction) -> Turn {
+        match (other.index() + 4 - self.index()) % 4 {
+            0 => Turn::Straight,
+            1 => Turn::Right,
+            2 => Turn::Back,
+            _ => Turn::Left,
+        }
+    }
+
+    /// Whether the direction lies on the x axis (east or west).
+    pub const fn is_horizontal(self) -> bool {
+        matches!(self, Direction::East | Direction::West)
+    }
+
+    /// Whether the direction lies on the y axis (north or south).
+    pub const fn is_vertical(self) -> bool {
+        !self.is_horizontal()
+    }
+}
+
+impl Direction8 {
+    /// All directions, clockwise from north, in the order of [`Direction8::index`].
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    /// Returns a compact index in `0..8`, to be used for lookup tables.
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Returns the direction with the given index, wrapping around.
+    pub const fn from_index(index: usize) -> Direction8 {
+        Self::ALL[index % 8]
+    }
+
+    /// Returns the vector pointing in this direction, with components of -1, 0 or 1.
+    pub const fn coords(self) -> Coords {
+        match self {
+            Direction8::North => Coords { x: 0, y: -1 },
+            Direction8::NorthEast => Coords { x: 1, y: -1 },
+            Direction8::East => Coords { x: 1, y: 0 },
+            Direction8::SouthEast => Coords { x: 1, y: 1 },
+            Direction8::South => Coords { x: 0, y: 1 },
+            Direction8::SouthWest => Coords { x: -1, y: 1 },
+            Direction8::West => Coords { x: -1, y: 0 },
+            Direction8::NorthWest => Coords { x: -1, y: -1 },
+        }
+    }
+
+    /// Turns by 45 degrees counter-clockwise.
+    pub const fn turn_left(self) -> Direction8 {
+        Self::from_index(self.index() + 7)
+    }
+
+    /// Turns by 45 degrees clockwise.
+    pub const fn turn_right(self) -> Direction8 {
+        Self::from_index(self.index() + 1)
+    }
+
+    pub const fn reverse(self) -> Direction8 {
+        Self::from_index(self.index() + 4)
+    }
+
+    /// Whether this is one of the four cardinal directions.
+    pub const fn is_cardinal(self) -> bool {
+        self.index() % 2 == 0
+    }
+}
+
+impl Turn {
+    /// Returns the turn that undoes this one.
+    pub const fn inverse(self) -> Turn {
+        match self {
+            Turn::Right => Turn::Left,
+            Turn::Left => Turn::Right,
+            turn => turn,
+        }
+    }
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl From<Direction> for Coords {
+    fn from(direction: Direction) -> Coords {
+        direction.coords()
+    }
+}
+
+impl From<Direction8> for Coords {
+    fn from(direction: Direction8) -> Coords {
+        direction.coords()
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Direction8 {
+        Direction8::from_index(2 * direction.index())
+    }
+}
+
+impl TryFrom<Direction8> for Direction {
+    type Error = ();
+
+    fn try_from(direction: Direction8) -> Result<Self, Self::Error> {
+        match direction.is_cardinal() {
+            true => Ok(Direction::from_index(direction.index() / 2)),
+            false => Err(()),
+        }
+    }
+}
+
+/// Accepts unit vectors only.
+impl TryFrom<Coords> for Direction {
+    type Error = ();
+
+    fn try_from(coords: Coords) -> Result<Self, Self::Error> {
+        Direction::ALL
+            .into_iter()
+            .find(|d| d.coords() == coords)
+            .ok_or(())
+    }
+}
+
+/// Accepts vectors with components of -1, 0 or 1, except the null vector.
+impl TryFrom<Coords> for Direction8 {
+    type Error = ();
+
+    fn try_from(coords: Coords) -> Result<Self, Self::Error> {
+        Direction8::ALL
+            .into_iter()
+            .find(|d| d.coords() == coords)
+            .ok_or(())
+    }
+}
+
+/// Parses the compass letters `NESW`, the relative letters `URDL` and the
+/// arrows `^>v<` and `↑→↓←`.
+impl TryFrom<char> for Direction {
+    type Error = ();
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            'N' | 'U' | '^' | '↑' => Ok(Direction::North),
+            'E' | 'R' | '>' | '→' => Ok(Direction::East),
+            'S' | 'D' | 'v' | '↓' => Ok(Direction::South),
+            'W' | 'L' | '<' | '←' => Ok(Direction::West),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Same as the implementation for [`char`], restricted to ASCII.
+impl TryFrom<u8> for Direction {
+    type Error = ();
+
+    fn try_from(byte: u8) -> Result<Self, Self::Error> {
+        match byte.is_ascii() {
+            true => Direction::try_from(byte as char),
+            false => Err(()),
+        }
+    }
+}
+
+impl Neg for Direction {
+    type Output = Direction;
+
+    fn neg(self) -> Self::Output {
+        self.reverse()
+    }
+}
+
+impl Mul<i64> for Direction {
+    type Output = Coords;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        self.coords() * rhs
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let arrow = match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        };
+
+        write!(f, "{arrow}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        use Direction::*;
+
+        assert_eq!(North.turn_left(), West);
+        assert_eq!(West.turn_right(), North);
+        assert_eq!(East.reverse(), West);
+        assert_eq!(South.turn(Turn::Left), East);
+
+        for a in Direction::ALL {
+            assert_eq!(a.turn_left().turn_right(), a);
+
+            for b in Direction::ALL {
+                assert_eq!(a.turn(a.turn_to(b)), b);
+                assert_eq!(b.turn(a.turn_to(b).inverse()), a);
+            }
+        }
+
+        assert_eq!(Direction8::NorthWest.turn_right(), Direction8::North);
+        assert_eq!(Direction8::SouthEast.reverse(), Direction8::NorthWest);
+    }
+
+    #[test]
+    fn test_coords() {
+        for d in Direction::ALL {
+            assert_eq!(d.coords().norm_l1(), 1);
+            assert_eq!(Direction::try_from(d.coords()), Ok(d));
+            assert_eq!(Direction::try_from(Direction8::from(d)), Ok(d));
+            assert_eq!(d.coords() + d.reverse().coords(), Coords::default());
+        }
+
+        assert_eq!(Direction::try_from(Coords::new(1, 1)), Err(()));
+        assert_eq!(
+            Direction8::try_from(Coords::new(1, 1)),
+            Ok(Direction8::SouthEast)
+        );
+        assert_eq!(Direction::try_from(Direction8::SouthWest), Err(()));
+        assert_eq!(Direction::West * 3, Coords::new(-3, 0));
+    }
+
+    #[test]
+    fn test_parse() {
+        let parse = |s: &str| {
+            s.chars()
+                .map(Direction::try_from)
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        assert_eq!(parse("NESW"), parse("URDL"));
+        assert_eq!(parse("^>v<"), parse("↑→↓←"));
+        assert_eq!(parse("NESW"), Ok(Direction::ALL.to_vec()));
+        assert_eq!(Direction::try_from(b'x'), Err(()));
+        assert_eq!(Direction::ALL.map(|d| d.to_string()).concat(), "^>v<");
+    }
+}
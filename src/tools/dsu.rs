@@ -0,0 +1,226 @@
+//! Disjoint sets, also known as union-find, to track connected components while
+//! edges are being added.
+//!
+//! ```
+//! use advent_of_code::tools::{atom::AtomTable, dsu::DisjointSet};
+//!
+//! let mut names = AtomTable::new();
+//! let [a, b, c, d] = ["a", "b", "c", "d"].map(|name| names.create(name));
+//!
+//! let mut sets = DisjointSet::new(names.len());
+//! sets.union(a, b);
+//! sets.union(c, b);
+//!
+//! assert!(sets.same(a, c));
+//! assert_eq!(sets.size(a), 3);
+//! assert_eq!(sets.members(d).collect::<Vec<_>>(), [d]);
+//! ```
+
+use std::marker::PhantomData;
+
+use super::atom::Atom;
+
+/// An element of a [`DisjointSet`], which is an index from 0 to `len - 1`.
+pub trait Element: Copy {
+    fn index(self) -> usize;
+    fn from_index(index: usize) -> Self;
+}
+
+/// A partition of elements into disjoint sets, with path compression and union
+/// by size so that operations take almost constant amortised time.
+#[derive(Clone, Debug)]
+pub struct DisjointSet<E = usize> {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    /// The members of each set form a cycle, so that they can be listed.
+    next: Vec<usize>,
+    count: usize,
+    marker: PhantomData<E>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<E: Element> DisjointSet<E> {
+    /// Creates `len` singleton sets.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            next: (0..len).collect(),
+            count: len,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Returns the number of sets.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Adds a new element in its own set.
+    pub fn push(&mut self) -> E {
+        let index = self.len();
+
+        self.parents.push(index);
+        self.sizes.push(1);
+        self.next.push(index);
+        self.count += 1;
+
+        E::from_index(index)
+    }
+
+    /// Returns the representative of the set of an element.
+    pub fn find(&mut self, element: E) -> E {
+        E::from_index(self.find_index(element.index()))
+    }
+
+    /// Merges the sets of both elements. Returns false if they were already
+    /// in the same set.
+    pub fn union(&mut self, a: E, b: E) -> bool {
+        let (mut a, mut b) = (self.find_index(a.index()), self.find_index(b.index()));
+
+        if a == b {
+            return false;
+        }
+
+        if self.sizes[a] < self.sizes[b] {
+            (a, b) = (b, a);
+        }
+
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        self.next.swap(a, b);
+        self.count -= 1;
+
+        true
+    }
+
+    pub fn same(&mut self, a: E, b: E) -> bool {
+        self.find_index(a.index()) == self.find_index(b.index())
+    }
+
+    /// Returns the size of the set of an element.
+    pub fn size(&mut self, element: E) -> usize {
+        let root = self.find_index(element.index());
+        self.sizes[root]
+    }
+
+    /// Iterates over the members of the set of an element, starting with it.
+    pub fn members(&self, element: E) -> impl Iterator<Item = E> + '_ {
+        let start = element.index();
+        let mut current = Some(start);
+
+        std::iter::from_fn(move || {
+            let index = current?;
+            let next = self.next[index];
+            current = (next != start).then_some(next);
+            Some(E::from_index(index))
+        })
+    }
+
+    /// Iterates over one representative of each set.
+    pub fn roots(&self) -> impl Iterator<Item = E> + '_ {
+        (0..self.len())
+            .filter(|&index| self.parents[index] == index)
+            .map(E::from_index)
+    }
+
+    /// Iterates over the sizes of the sets.
+    pub fn sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.roots().map(|root| self.sizes[root.index()])
+    }
+
+    /// Returns the members of every set.
+    pub fn sets(&self) -> Vec<Vec<E>> {
+        self.roots()
+            .map(|root| self.members(root).collect())
+            .collect()
+    }
+
+    fn find_index(&mut self, mut index: usize) -> usize {
+        // Path halving: point every other node on the path to its grandparent
+        while self.parents[index] != index {
+            let grandparent = self.parents[self.parents[index]];
+            self.parents[index] = grandparent;
+            index = grandparent;
+        }
+
+        index
+    }
+}
+
+impl Element for usize {
+    fn index(self) -> usize {
+        self
+    }
+
+    fn from_index(index: usize) -> Self {
+        index
+    }
+}
+
+impl<Tag> Element for Atom<Tag> {
+    fn index(self) -> usize {
+        Atom::index(self)
+    }
+
+    fn from_index(index: usize) -> Self {
+        Atom::from_index(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_union_find() {
+        let mut sets: DisjointSet = DisjointSet::new(8);
+
+        assert!(sets.union(0, 1));
+        assert!(sets.union(2, 3));
+        assert!(sets.union(1, 3));
+        assert!(!sets.union(0, 2));
+        assert!(sets.union(5, 6));
+
+        assert_eq!(sets.count(), 4);
+        assert!(sets.same(0, 3) && !sets.same(0, 5));
+        assert_eq!(sets.size(2), 4);
+
+        let mut sizes: Vec<_> = sets.sizes().collect();
+        sizes.sort();
+        assert_eq!(sizes, [1, 1, 2, 4]);
+
+        let extra = sets.push();
+        sets.union(extra, 7);
+        assert_eq!(sets.size(7), 2);
+        assert_eq!(sets.count(), 4);
+    }
+
+    #[test]
+    fn test_members() {
+        let mut sets: DisjointSet = DisjointSet::new(6);
+
+        for (a, b) in [(0, 2), (4, 2), (1, 5)] {
+            sets.union(a, b);
+        }
+
+        let mut members: Vec<_> = sets.members(2).collect();
+        members.sort();
+        assert_eq!(members, [0, 2, 4]);
+        assert_eq!(sets.members(3).collect::<Vec<_>>(), [3]);
+
+        let mut all: Vec<_> = sets.sets().into_iter().flatten().collect();
+        all.sort();
+        assert_eq!(all, [0, 1, 2, 3, 4, 5]);
+    }
+}
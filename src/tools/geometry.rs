@@ -0,0 +1,296 @@
+//! Exact intersections of lines and segments in 2D and 3D. Coordinates are
+//! widened to [`i128`] and results are [`Rational`], so large inputs neither
+//! overflow nor suffer from rounding.
+
+use std::fmt::Debug;
+
+use super::{rational::Rational, Coords, Coords3, Vector};
+
+/// Coordinates that can be handled exactly, by embedding them into 3D.
+pub trait Embed: Vector {
+    /// A point with rational coordinates in the same space.
+    type Point: Copy + Debug + PartialEq;
+
+    fn embed(self) -> [i128; 3];
+    fn point(components: [Rational; 3]) -> Self::Point;
+}
+
+/// The line of points `point + t * direction`, for any rational `t`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Line<V> {
+    pub point: V,
+    pub direction: V,
+}
+
+/// The segment between two points, both included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Segment<V> {
+    pub start: V,
+    pub end: V,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineIntersection {
+    /// The lines meet at a single point, at parameter `t` on the first line
+    /// and `s` on the second.
+    Crossing { t: Rational, s: Rational },
+    /// The lines are parallel, or skew in 3D.
+    Disjoint,
+    /// The lines are the same.
+    Coincident,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentIntersection<P> {
+    Point(P),
+    /// The segments are collinear and share more than one point.
+    Overlap,
+    None,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<V: Embed> Line<V> {
+    pub fn new(point: V, direction: V) -> Self {
+        Self { point, direction }
+    }
+
+    /// Returns the point at parameter `t`.
+    pub fn at(&self, t: Rational) -> V::Point {
+        let (p, d) = (self.point.embed(), self.direction.embed());
+        V::point([0, 1, 2].map(|i| Rational::integer(p[i]) + t * Rational::integer(d[i])))
+    }
+
+    pub fn intersection(&self, other: &Line<V>) -> LineIntersection {
+        let (p, d) = (self.point.embed(), self.direction.embed());
+        let (q, e) = (other.point.embed(), other.direction.embed());
+        let offset = sub(q, p);
+
+        // Solve p + t * d = q + s * e by crossing both sides with e and d, which
+        // gives (q - p) x e = t * (d x e) and (q - p) x d = s * (d x e)
+        let normal = cross(d, e);
+
+        if normal == [0; 3] {
+            return match cross(offset, d) == [0; 3] {
+                true => LineIntersection::Coincident,
+                false => LineIntersection::Disjoint,
+            };
+        }
+
+        if dot(offset, normal) != 0 {
+            return LineIntersection::Disjoint;
+        }
+
+        // Any non-zero component gives the ratio, the largest is as good as any
+        let axis = (0..3).max_by_key(|&i| normal[i].unsigned_abs()).unwrap();
+
+        LineIntersection::Crossing {
+            t: Rational::new(cross(offset, e)[axis], normal[axis]),
+            s: Rational::new(cross(offset, d)[axis], normal[axis]),
+        }
+    }
+}
+
+impl<V: Embed> Segment<V> {
+    pub fn new(start: V, end: V) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the line through the segment, where the segment goes from
+    /// parameter 0 to 1.
+    pub fn line(&self) -> Line<V> {
+        Line::new(self.start, self.end - self.start)
+    }
+
+    pub fn contains(&self, point: V) -> bool {
+        let (start, end, point) = (self.start.embed(), self.end.embed(), point.embed());
+        let (direction, offset) = (sub(end, start), sub(point, start));
+
+        cross(direction, offset) == [0; 3]
+            && (0..=dot(direction, direction)).contains(&dot(offset, direction))
+    }
+
+    pub fn intersection(&self, other: &Segment<V>) -> SegmentIntersection<V::Point> {
+        let unit = Rational::ZERO..=Rational::ONE;
+
+        match self.line().intersection(&other.line()) {
+            LineIntersection::Crossing { t, s } if unit.contains(&t) && unit.contains(&s) => {
+                SegmentIntersection::Point(self.line().at(t))
+            }
+
+            LineIntersection::Coincident => self.collinear_intersection(other),
+
+            // Points have a zero direction, and are parallel to every line
+            LineIntersection::Disjoint if self.start == self.end => {
+                match other.contains(self.start) {
+                    true => SegmentIntersection::Point(self.line().at(Rational::ZERO)),
+                    false => SegmentIntersection::None,
+                }
+            }
+
+            LineIntersection::Disjoint if other.start == other.end => other.intersection(self),
+
+            _ => SegmentIntersection::None,
+        }
+    }
+
+    /// Intersects segments on the same line, by projecting the other segment
+    /// onto this one.
+    fn collinear_intersection(&self, other: &Segment<V>) -> SegmentIntersection<V::Point> {
+        if self.start == self.end {
+            return match other.contains(self.start) {
+                true => SegmentIntersection::Point(self.line().at(Rational::ZERO)),
+                false => SegmentIntersection::None,
+            };
+        }
+
+        let start = self.start.embed();
+        let direction = sub(self.end.embed(), start);
+        let length = dot(direction, direction);
+
+        let project = |point: V| dot(sub(point.embed(), start), direction);
+        let (a, b) = (project(other.start), project(other.end));
+        let (low, high) = (a.min(b).max(0), a.max(b).min(length));
+
+        match low.cmp(&high) {
+            std::cmp::Ordering::Less => SegmentIntersection::Overlap,
+            std::cmp::Ordering::Equal => {
+                SegmentIntersection::Point(self.line().at(Rational::new(low, length)))
+            }
+            std::cmp::Ordering::Greater => SegmentIntersection::None,
+        }
+    }
+}
+
+impl Embed for Coords {
+    type Point = [Rational; 2];
+
+    fn embed(self) -> [i128; 3] {
+        [self.x as i128, self.y as i128, 0]
+    }
+
+    fn point([x, y, _]: [Rational; 3]) -> Self::Point {
+        [x, y]
+    }
+}
+
+impl Embed for Coords3 {
+    type Point = [Rational; 3];
+
+    fn embed(self) -> [i128; 3] {
+        [self.x as i128, self.y as i128, self.z as i128]
+    }
+
+    fn point(components: [Rational; 3]) -> Self::Point {
+        components
+    }
+}
+
+fn sub(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+/// Returns the dot product of two embedded vectors.
+pub fn dot(a: [i128; 3], b: [i128; 3]) -> i128 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Returns the cross product of two embedded vectors.
+pub fn cross(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(n: i128, d: i128) -> Rational {
+        Rational::new(n, d)
+    }
+
+    #[test]
+    fn test_lines_2d() {
+        // Hailstones A and B of the Day 24 example, ignoring z
+        let a = Line::new(Coords::new(19, 13), Coords::new(-2, 1));
+        let b = Line::new(Coords::new(18, 19), Coords::new(-1, -1));
+
+        let LineIntersection::Crossing { t, s } = a.intersection(&b) else {
+            panic!("the paths should cross");
+        };
+
+        assert_eq!(a.at(t), [r(43, 3), r(46, 3)]);
+        assert_eq!(a.at(t), b.at(s));
+        assert!(t > Rational::ZERO && s > Rational::ZERO);
+
+        let c = Line::new(Coords::new(20, 25), Coords::new(-2, -2));
+        assert_eq!(b.intersection(&c), LineIntersection::Disjoint);
+        assert_eq!(
+            b.intersection(&Line::new(Coords::new(0, 1), Coords::new(3, 3))),
+            LineIntersection::Coincident
+        );
+    }
+
+    #[test]
+    fn test_lines_3d() {
+        let a = Line::new(Coords3::new(0, 0, 0), Coords3::new(1, 1, 1));
+        let b = Line::new(Coords3::new(4, 0, 2), Coords3::new(-1, 1, 0));
+        let skew = Line::new(Coords3::new(4, 0, 3), Coords3::new(-1, 1, 0));
+
+        assert_eq!(
+            a.intersection(&b),
+            LineIntersection::Crossing {
+                t: r(2, 1),
+                s: r(2, 1)
+            }
+        );
+        assert_eq!(a.intersection(&skew), LineIntersection::Disjoint);
+
+        // Products that overflow i64
+        let big = 400_000_000_000_000;
+        let c = Line::new(Coords3::new(big, 0, 0), Coords3::new(-1, 1, 0));
+        let d = Line::new(Coords3::new(0, 0, 0), Coords3::new(big, big, 0));
+        assert_eq!(
+            c.intersection(&d),
+            LineIntersection::Crossing {
+                t: r(big as i128 / 2, 1),
+                s: r(1, 2)
+            }
+        );
+    }
+
+    #[test]
+    fn test_segments() {
+        let segment = |a: (i64, i64), b: (i64, i64)| Segment::new(Coords::from(a), Coords::from(b));
+        let base = segment((0, 0), (4, 0));
+
+        assert_eq!(
+            base.intersection(&segment((1, -1), (2, 1))),
+            SegmentIntersection::Point([r(3, 2), r(0, 1)])
+        );
+        assert_eq!(
+            base.intersection(&segment((1, 1), (2, 3))),
+            SegmentIntersection::None
+        );
+        assert_eq!(
+            base.intersection(&segment((6, 0), (3, 0))),
+            SegmentIntersection::Overlap
+        );
+        assert_eq!(
+            base.intersection(&segment((6, 0), (4, 0))),
+            SegmentIntersection::Point([r(4, 1), r(0, 1)])
+        );
+        assert_eq!(
+            base.intersection(&segment((5, 0), (7, 0))),
+            SegmentIntersection::None
+        );
+        assert_eq!(
+            base.intersection(&segment((2, 0), (2, 0))),
+            SegmentIntersection::Point([r(2, 1), r(0, 1)])
+        );
+        assert!(base.contains(Coords::new(4, 0)) && !base.contains(Coords::new(5, 0)));
+    }
+}
@@ -0,0 +1,527 @@
+//! Graphs of named nodes, stored as adjacency lists of [`Atom`]s, with the
+//! usual algorithms: connected components, topological sort, maximum flow and
+//! minimum cuts.
+//!
+//! ```
+//! use advent_of_code::tools::graph::{Graph, GraphBuilder};
+//!
+//! let graph: Graph = GraphBuilder::undirected()
+//!     .lines("a: b c\nb: c\nd: e", ": ")
+//!     .unwrap()
+//!     .build();
+//!
+//! let (a, b, e) = (graph.atom("a").unwrap(), graph.atom("b").unwrap(), graph.atom("e").unwrap());
+//! assert_eq!(graph.neighbours(a).len(), 2);
+//! assert_eq!(graph.components().count(), 2);
+//! assert_eq!(graph.max_flow(a, b), 2);
+//! assert_eq!(graph.max_flow(a, e), 0);
+//! ```
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    fmt::Write,
+};
+
+use super::{
+    atom::{Atom, AtomTable, AtomVec},
+    dsu::DisjointSet,
+    parse::{lines, Span},
+};
+use crate::ParseResult;
+
+/// Whether edges go one way, or both ways.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    Directed,
+    Undirected,
+}
+
+/// Collects nodes and edges, and then builds a [`Graph`].
+pub struct GraphBuilder<'a, Tag = ()> {
+    names: AtomTable<&'a str, Tag>,
+    adjacency: AtomVec<Vec<Atom<Tag>>, Tag>,
+    orientation: Orientation,
+}
+
+/// A graph with nodes named by strings and unweighted edges. Multiple edges
+/// between the same nodes are merged.
+pub struct Graph<'a, Tag = ()> {
+    names: AtomTable<&'a str, Tag>,
+    adjacency: AtomVec<Vec<Atom<Tag>>, Tag>,
+    orientation: Orientation,
+}
+
+/// A cut of the nodes in two sides, with the number of edges between them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cut<Tag = ()> {
+    pub weight: usize,
+    /// The nodes on one side of the cut, the other side being the rest.
+    pub side: Vec<Atom<Tag>>,
+}
+
+/// A residual network with unit capacities, where arc `i ^ 1` is the reverse
+/// of arc `i`.
+struct Network {
+    arcs: Vec<Vec<usize>>,
+    targets: Vec<usize>,
+    capacities: Vec<usize>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<'a> GraphBuilder<'a> {
+    pub fn directed() -> Self {
+        Self::new(Orientation::Directed)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(Orientation::Undirected)
+    }
+}
+
+impl<'a, Tag> GraphBuilder<'a, Tag> {
+    /// Creates an empty builder, which can be tagged unlike [`directed`] and
+    /// [`undirected`].
+    ///
+    /// [`directed`]: GraphBuilder::directed
+    /// [`undirected`]: GraphBuilder::undirected
+    pub fn new(orientation: Orientation) -> Self {
+        Self {
+            names: AtomTable::default(),
+            adjacency: AtomVec::new(),
+            orientation,
+        }
+    }
+
+    /// Returns the node with the given name, adding it if it is new.
+    pub fn node(&mut self, name: &'a str) -> Atom<Tag> {
+        let atom = self.names.create(name);
+
+        if atom.index() == self.adjacency.len() {
+            self.adjacency.insert(atom, Vec::new());
+        }
+
+        atom
+    }
+
+    /// Adds an edge, unless there already is one.
+    pub fn edge(&mut self, from: Atom<Tag>, to: Atom<Tag>) {
+        if self.adjacency[from].contains(&to) {
+            return;
+        }
+
+        self.adjacency[from].push(to);
+
+        if self.orientation == Orientation::Undirected && from != to {
+            self.adjacency[to].push(from);
+        }
+    }
+
+    /// Adds a `name<separator>neighbours` line, such as `jqt: rhn xhk nvd` or
+    /// `a -> b, c`, and returns the node it describes.
+    pub fn line(&mut self, line: Span<'a>, separator: &'static str) -> ParseResult<Atom<Tag>> {
+        let (name, neighbours) = line.key_values(separator)?;
+
+        if name.is_empty() {
+            return Err(name.expected("a node name"));
+        }
+
+        let from = self.node(name.as_str());
+
+        for neighbour in neighbours {
+            let to = self.node(neighbour.as_str());
+            self.edge(from, to);
+        }
+
+        Ok(from)
+    }
+
+    /// Adds every non-empty line of the input with [`GraphBuilder::line`].
+    pub fn lines(mut self, input: &'a str, separator: &'static str) -> ParseResult<Self> {
+        for line in lines(input) {
+            self.line(line, separator)?;
+        }
+
+        Ok(self)
+    }
+
+    pub fn build(self) -> Graph<'a, Tag> {
+        Graph {
+            names: self.names,
+            adjacency: self.adjacency,
+            orientation: self.orientation,
+        }
+    }
+}
+
+impl<'a, Tag> Graph<'a, Tag> {
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adjacency.is_empty()
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    /// Returns the node with the given name.
+    pub fn atom(&self, name: &str) -> Option<Atom<Tag>> {
+        self.names.get(name)
+    }
+
+    /// Returns the name of a node.
+    pub fn name(&self, node: Atom<Tag>) -> &'a str {
+        self.names.resolve(node)
+    }
+
+    pub fn nodes(&self) -> impl ExactSizeIterator<Item = Atom<Tag>> {
+        (0..self.len()).map(Atom::from_index)
+    }
+
+    /// Returns the nodes that the node has an edge to.
+    pub fn neighbours(&self, node: Atom<Tag>) -> &[Atom<Tag>] {
+        &self.adjacency[node]
+    }
+
+    /// Iterates over the edges, only once each for undirected graphs.
+    pub fn edges(&self) -> impl Iterator<Item = (Atom<Tag>, Atom<Tag>)> + '_ {
+        self.nodes().flat_map(move |from| {
+            self.adjacency[from]
+                .iter()
+                .filter(move |&&to| self.orientation == Orientation::Directed || from <= to)
+                .map(move |&to| (from, to))
+        })
+    }
+
+    /// Returns the connected components, ignoring the direction of edges.
+    pub fn components(&self) -> DisjointSet<Atom<Tag>> {
+        let mut sets = DisjointSet::new(self.len());
+
+        for (from, to) in self.edges() {
+            sets.union(from, to);
+        }
+
+        sets
+    }
+
+    /// Returns the nodes ordered so that every edge goes forward, or [`None`]
+    /// if there is a cycle. Any edge of an undirected graph is a cycle.
+    pub fn topological_sort(&self) -> Option<Vec<Atom<Tag>>> {
+        if self.orientation == Orientation::Undirected {
+            return self
+                .edges()
+                .next()
+                .is_none()
+                .then(|| self.nodes().collect());
+        }
+
+        let mut incoming = AtomVec::<usize, Tag>::filled(self.len(), 0);
+
+        for (_, to) in self.edges() {
+            incoming[to] += 1;
+        }
+
+        // Kahn's algorithm: repeatedly take a node that has no incoming edge left
+        let mut ready: Vec<_> = self.nodes().filter(|&node| incoming[node] == 0).collect();
+        let mut order = Vec::with_capacity(self.len());
+
+        while let Some(node) = ready.pop() {
+            order.push(node);
+
+            for &next in &self.adjacency[node] {
+                incoming[next] -= 1;
+
+                if incoming[next] == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        (order.len() == self.len()).then_some(order)
+    }
+
+    /// Returns the maximum number of edge-disjoint paths from the source to
+    /// the sink.
+    pub fn max_flow(&self, source: Atom<Tag>, sink: Atom<Tag>) -> usize {
+        self.network().max_flow(source.index(), sink.index()).0
+    }
+
+    /// Returns the fewest edges to remove so that the sink can't be reached
+    /// from the source. The side of the cut is the one of the source.
+    pub fn min_cut(&self, source: Atom<Tag>, sink: Atom<Tag>) -> Cut<Tag> {
+        let (weight, reachable) = self.network().max_flow(source.index(), sink.index());
+
+        Cut {
+            weight,
+            side: self
+                .nodes()
+                .filter(|node| reachable[node.index()])
+                .collect(),
+        }
+    }
+
+    /// Returns the fewest edges to remove to disconnect the graph, ignoring
+    /// their direction, using the Stoer–Wagner algorithm. Returns [`None`] if
+    /// there are less than two nodes.
+    pub fn global_min_cut(&self) -> Option<Cut<Tag>> {
+        let mut weights: Vec<HashMap<usize, usize>> = vec![HashMap::new(); self.len()];
+
+        for (from, to) in self.edges().filter(|(from, to)| from != to) {
+            *weights[from.index()].entry(to.index()).or_default() += 1;
+            *weights[to.index()].entry(from.index()).or_default() += 1;
+        }
+
+        let mut groups: Vec<Vec<usize>> = (0..self.len()).map(|node| vec![node]).collect();
+        let mut active: Vec<usize> = (0..self.len()).collect();
+        let mut best: Option<(usize, Vec<usize>)> = None;
+
+        while active.len() > 1 {
+            // Maximum adjacency search: add the node most tightly connected to
+            // the nodes added so far, until the last two are found
+            let mut connection = vec![0; self.len()];
+            let mut added = vec![false; self.len()];
+            let mut queue = BinaryHeap::from([(0, Reverse(active[0]))]);
+            let mut order = Vec::with_capacity(active.len());
+
+            while let Some((weight, Reverse(node))) = queue.pop() {
+                if added[node] || weight != connection[node] {
+                    continue;
+                }
+
+                added[node] = true;
+                order.push(node);
+
+                for (&next, &weight) in &weights[node] {
+                    if !added[next] {
+                        connection[next] += weight;
+                        queue.push((connection[next], Reverse(next)));
+                    }
+                }
+            }
+
+            if order.len() < active.len() {
+                let side = order.iter().flat_map(|&node| &groups[node]).copied();
+                best = Some((0, side.collect()));
+                break;
+            }
+
+            let (s, t) = (order[order.len() - 2], order[order.len() - 1]);
+
+            if best
+                .as_ref()
+                .map_or(true, |(weight, _)| connection[t] < *weight)
+            {
+                best = Some((connection[t], groups[t].clone()));
+            }
+
+            // Merge t into s
+            let merged = std::mem::take(&mut groups[t]);
+            groups[s].extend(merged);
+
+            for (next, weight) in std::mem::take(&mut weights[t]) {
+                weights[next].remove(&t);
+
+                if next != s {
+                    *weights[s].entry(next).or_default() += weight;
+                    *weights[next].entry(s).or_default() += weight;
+                }
+            }
+
+            active.retain(|&node| node != t);
+        }
+
+        best.map(|(weight, side)| Cut {
+            weight,
+            side: side.into_iter().map(Atom::from_index).collect(),
+        })
+    }
+
+    /// Returns the graph in the DOT language of Graphviz.
+    pub fn to_dot(&self) -> String {
+        let (keyword, arrow) = match self.orientation {
+            Orientation::Directed => ("digraph", "->"),
+            Orientation::Undirected => ("graph", "--"),
+        };
+
+        let mut dot = format!("{keyword} {{\n");
+
+        for node in self.nodes() {
+            writeln!(dot, "    {:?};", self.name(node)).unwrap();
+        }
+
+        for (from, to) in self.edges() {
+            writeln!(
+                dot,
+                "    {:?} {arrow} {:?};",
+                self.name(from),
+                self.name(to)
+            )
+            .unwrap();
+        }
+
+        dot.push('}');
+        dot
+    }
+
+    fn network(&self) -> Network {
+        let mut network = Network {
+            arcs: vec![Vec::new(); self.len()],
+            targets: Vec::new(),
+            capacities: Vec::new(),
+        };
+
+        let reverse = match self.orientation {
+            Orientation::Directed => 0,
+            Orientation::Undirected => 1,
+        };
+
+        for (from, to) in self.edges() {
+            network.add(from.index(), to.index(), 1, reverse);
+        }
+
+        network
+    }
+}
+
+impl Network {
+    fn add(&mut self, from: usize, to: usize, capacity: usize, reverse: usize) {
+        self.arcs[from].push(self.targets.len());
+        self.targets.push(to);
+        self.capacities.push(capacity);
+
+        self.arcs[to].push(self.targets.len());
+        self.targets.push(from);
+        self.capacities.push(reverse);
+    }
+
+    /// Runs the Edmonds–Karp algorithm, and returns the flow along with the
+    /// nodes that can still be reached from the source.
+    fn max_flow(&mut self, source: usize, sink: usize) -> (usize, Vec<bool>) {
+        let mut flow = 0;
+
+        loop {
+            let mut through = vec![None; self.arcs.len()];
+            let mut reached = vec![false; self.arcs.len()];
+            let mut queue = VecDeque::from([source]);
+            reached[source] = true;
+
+            while let Some(node) = queue.pop_front() {
+                for &arc in &self.arcs[node] {
+                    let next = self.targets[arc];
+
+                    if self.capacities[arc] > 0 && !reached[next] {
+                        reached[next] = true;
+                        through[next] = Some(arc);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            if !reached[sink] || source == sink {
+                return (flow, reached);
+            }
+
+            let mut path = Vec::new();
+            let mut node = sink;
+
+            while let Some(arc) = through[node] {
+                path.push(arc);
+                node = self.targets[arc ^ 1];
+            }
+
+            let bottleneck = path.iter().map(|&arc| self.capacities[arc]).min().unwrap();
+
+            for arc in path {
+                self.capacities[arc] -= bottleneck;
+                self.capacities[arc ^ 1] += bottleneck;
+            }
+
+            flow += bottleneck;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+";
+
+    #[test]
+    fn test_cuts() {
+        let graph: Graph = GraphBuilder::undirected()
+            .lines(EXAMPLE, ": ")
+            .unwrap()
+            .build();
+
+        assert_eq!(graph.len(), 15);
+        assert_eq!(graph.edges().count(), 33);
+        assert_eq!(graph.components().count(), 1);
+
+        let cut = graph.global_min_cut().unwrap();
+        assert_eq!(cut.weight, 3);
+        assert_eq!(cut.side.len() * (graph.len() - cut.side.len()), 54);
+
+        let (hfx, pzl) = (graph.atom("hfx").unwrap(), graph.atom("pzl").unwrap());
+        let (jqt, cmg) = (graph.atom("jqt").unwrap(), graph.atom("cmg").unwrap());
+        let (rhn, xhk) = (graph.atom("rhn").unwrap(), graph.atom("xhk").unwrap());
+        assert_eq!(graph.max_flow(hfx, pzl), 3);
+        assert!(graph.max_flow(rhn, xhk) > 3);
+
+        let cut = graph.min_cut(jqt, cmg);
+        assert_eq!(cut.weight, 3);
+        assert!(cut.side.contains(&jqt) && !cut.side.contains(&cmg));
+        assert!([6, 9].contains(&cut.side.len()));
+    }
+
+    #[test]
+    fn test_directed() {
+        let mut builder = GraphBuilder::directed();
+        builder.node("lonely");
+        let graph: Graph = builder
+            .lines("a -> b, c\nb -> d\nc -> d", " -> ")
+            .unwrap()
+            .build();
+
+        let order = graph.topological_sort().unwrap();
+        let position = |name| order.iter().position(|&node| graph.name(node) == name);
+        assert!(position("a") < position("b") && position("b") < position("d"));
+        assert!(position("c") < position("d"));
+
+        let (a, d) = (graph.atom("a").unwrap(), graph.atom("d").unwrap());
+        assert_eq!(graph.max_flow(a, d), 2);
+        assert_eq!(graph.max_flow(d, a), 0);
+        assert_eq!(graph.components().count(), 2);
+
+        let cyclic: Graph = GraphBuilder::directed()
+            .lines("a -> b\nb -> a", " -> ")
+            .unwrap()
+            .build();
+        assert_eq!(cyclic.topological_sort(), None);
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph {\n    \"lonely\";\n    \"a\";\n    \"b\";\n    \"c\";\n    \"d\";\n    \"a\" -> \"b\";\n    \"a\" -> \"c\";\n    \
+             \"b\" -> \"d\";\n    \"c\" -> \"d\";\n}"
+        );
+    }
+}
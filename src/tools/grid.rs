@@ -0,0 +1,355 @@
+use std::{
+    fmt::Display,
+    ops::{Index, IndexMut},
+};
+
+use super::{
+    parse::{self, Span},
+    Coords, Direction, Direction8, UCoords,
+};
+use crate::ParseResult;
+
+/// A rectangular 2D grid, stored as a flat vector in row-major order.
+///
+/// # Display
+/// Each cell is displayed in turn, with a new line after each row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    size: UCoords,
+}
+
+/// A position that can be used to access a cell of a [`Grid`]. Signed
+/// coordinates may fall outside of the grid.
+pub trait Position: Copy {
+    /// Returns the unsigned coordinates, if they are within the bounds.
+    fn in_bounds(self, size: &UCoords) -> Option<UCoords>;
+}
+
+impl Position for UCoords {
+    fn in_bounds(self, size: &UCoords) -> Option<UCoords> {
+        (self.x < size.x && self.y < size.y).then_some(self)
+    }
+}
+
+impl Position for Coords {
+    fn in_bounds(self, size: &UCoords) -> Option<UCoords> {
+        self.ucoords(size)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid from cells in row-major order.
+    ///
+    /// # Panics
+    /// If the number of cells does not match the size.
+    pub fn new(size: UCoords, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), size.x * size.y, "grid size mismatch");
+        Self { cells, size }
+    }
+
+    /// Creates a grid where every cell has the same value.
+    pub fn filled(size: UCoords, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(size, vec![value; size.x * size.y])
+    }
+
+    /// Parses a grid, decoding each byte with the `cell` function. When it returns
+    /// [`None`], the error says that `what` was expected.
+    pub fn parse_with(
+        input: &str,
+        what: &'static str,
+        cell: impl Fn(u8) -> Option<T>,
+    ) -> ParseResult<Self> {
+        let (cells, size) = parse::grid(input, what, cell)?;
+        Ok(Self { cells, size })
+    }
+
+    /// Same as [`Grid::parse_with`], for lines that have already been split.
+    pub fn from_lines(
+        lines: &[Span],
+        what: &'static str,
+        cell: impl Fn(u8) -> Option<T>,
+    ) -> ParseResult<Self> {
+        let (cells, size) = parse::grid_from_lines(lines, what, cell)?;
+        Ok(Self { cells, size })
+    }
+
+    pub fn size(&self) -> UCoords {
+        self.size
+    }
+
+    pub fn width(&self) -> usize {
+        self.size.x
+    }
+
+    pub fn height(&self) -> usize {
+        self.size.y
+    }
+
+    /// Returns all cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    pub fn contains(&self, at: impl Position) -> bool {
+        at.in_bounds(&self.size).is_some()
+    }
+
+    pub fn get(&self, at: impl Position) -> Option<&T> {
+        let at = at.in_bounds(&self.size)?;
+        self.cells.get(self.index_of(at))
+    }
+
+    pub fn get_mut(&mut self, at: impl Position) -> Option<&mut T> {
+        let at = at.in_bounds(&self.size)?;
+        let index = self.index_of(at);
+        self.cells.get_mut(index)
+    }
+
+    /// Returns the index of the cell in the underlying vector.
+    pub fn index_of(&self, at: UCoords) -> usize {
+        at.y * self.size.x + at.x
+    }
+
+    /// Returns the coordinates of the cell at an index of the underlying vector.
+    pub fn coords_of(&self, index: usize) -> UCoords {
+        UCoords::new(index % self.size.x, index / self.size.x)
+    }
+
+    /// Returns the coordinates of the first cell that satisfies the predicate.
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<UCoords> {
+        let index = self.cells.iter().position(predicate)?;
+        Some(self.coords_of(index))
+    }
+
+    /// Returns the coordinates of the adjacent cell in the given direction, if
+    /// it is within the bounds.
+    pub fn step(&self, at: UCoords, direction: Direction) -> Option<UCoords> {
+        (Coords::from(at) + direction.coords()).ucoords(&self.size)
+    }
+
+    /// Returns the up to 4 orthogonally adjacent cells within the bounds,
+    /// clockwise from north.
+    pub fn neighbours(&self, at: UCoords) -> impl Iterator<Item = UCoords> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |direction| self.step(at, direction))
+    }
+
+    /// Returns the up to 8 cells that surround the given cell within the bounds,
+    /// including diagonals, clockwise from north.
+    pub fn neighbours_diagonal(&self, at: UCoords) -> impl Iterator<Item = UCoords> + '_ {
+        Direction8::ALL.into_iter().filter_map(move |direction| {
+            (Coords::from(at) + direction.coords()).ucoords(&self.size)
+        })
+    }
+
+    /// Returns an iterator over all cells with their coordinates, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (UCoords, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (self.coords_of(i), cell))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.size.x..(y + 1) * self.size.x]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.size.x..(y + 1) * self.size.x]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + '_ {
+        self.cells.chunks_exact(self.size.x.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.cells[x..].iter().step_by(self.size.x)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> + '_ {
+        (0..self.size.x).map(|x| self.column(x))
+    }
+
+    /// Creates a new grid of the same size by mapping each cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            size: self.size,
+        }
+    }
+
+    /// Creates a new grid with the rows and columns swapped.
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.remap(UCoords::new(self.size.y, self.size.x), |at| {
+            UCoords::new(at.y, at.x)
+        })
+    }
+
+    /// Creates a new grid rotated by a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.size.y;
+        self.remap(UCoords::new(self.size.y, self.size.x), |at| {
+            UCoords::new(at.y, height - 1 - at.x)
+        })
+    }
+
+    /// Creates a new grid rotated by a quarter turn counter-clockwise.
+    pub fn rotate_counter_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.size.x;
+        self.remap(UCoords::new(self.size.y, self.size.x), |at| {
+            UCoords::new(width - 1 - at.y, at.x)
+        })
+    }
+
+    /// Mirrors the grid in place, swapping the left and right sides.
+    pub fn flip_horizontal(&mut self) {
+        for y in 0..self.size.y {
+            self.row_mut(y).reverse();
+        }
+    }
+
+    /// Mirrors the grid in place, swapping the top and bottom sides.
+    pub fn flip_vertical(&mut self) {
+        let width = self.size.x;
+
+        for y in 0..self.size.y / 2 {
+            let (top, bottom) = self.cells.split_at_mut((self.size.y - 1 - y) * width);
+            top[y * width..(y + 1) * width].swap_with_slice(&mut bottom[..width]);
+        }
+    }
+
+    /// Builds a grid of the given size, where each cell is copied from the
+    /// coordinates returned by `source`.
+    fn remap(&self, size: UCoords, source: impl Fn(UCoords) -> UCoords) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| UCoords::new(x, y)))
+            .map(|at| self[source(at)].clone())
+            .collect();
+
+        Grid { cells, size }
+    }
+}
+
+impl<T: From<u8>> Grid<T> {
+    /// Parses a grid, converting each byte into a cell.
+    pub fn parse(input: &str) -> ParseResult<Self> {
+        Self::parse_with(input, "a cell", |byte| Some(T::from(byte)))
+    }
+}
+
+impl<T> Index<UCoords> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, at: UCoords) -> &Self::Output {
+        &self.cells[self.index_of(at)]
+    }
+}
+
+impl<T> IndexMut<UCoords> for Grid<T> {
+    fn index_mut(&mut self, at: UCoords) -> &mut Self::Output {
+        let index = self.index_of(at);
+        &mut self.cells[index]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Grid<char> {
+        Grid::parse_with("abc\ndef", "a letter", |b| Some(b as char)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_access() {
+        let grid = sample();
+
+        assert_eq!(grid.size(), UCoords::new(3, 2));
+        assert_eq!(grid.get(UCoords::new(1, 1)), Some(&'e'));
+        assert_eq!(grid.get(Coords::new(-1, 0)), None);
+        assert_eq!(grid.get(UCoords::new(3, 0)), None);
+        assert_eq!(grid[UCoords::new(2, 0)], 'c');
+        assert_eq!(grid.position(|&c| c == 'd'), Some(UCoords::new(0, 1)));
+
+        let bytes = Grid::<u8>::parse("12\n34\n").unwrap();
+        assert_eq!(bytes.cells(), b"1234");
+        assert!(Grid::<u8>::parse("12\n3").is_err());
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = sample();
+        let corner = UCoords::new(0, 0);
+        let middle = UCoords::new(1, 0);
+
+        assert_eq!(grid.neighbours(corner).count(), 2);
+        assert_eq!(grid.neighbours_diagonal(corner).count(), 3);
+        assert_eq!(
+            grid.neighbours(middle).collect::<Vec<_>>(),
+            [(2, 0).into(), (1, 1).into(), (0, 0).into()]
+        );
+        assert_eq!(grid.neighbours_diagonal(middle).count(), 5);
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = sample();
+
+        assert_eq!(grid.row(1), ['d', 'e', 'f']);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.column(2).collect::<String>(), "cf");
+        assert_eq!(grid.columns().map(|c| c.count()).sum::<usize>(), 6);
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = sample();
+
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf\n");
+        assert_eq!(grid.rotate_clockwise().to_string(), "da\neb\nfc\n");
+        assert_eq!(grid.rotate_counter_clockwise().to_string(), "cf\nbe\nad\n");
+
+        let mut flipped = grid.clone();
+        flipped.flip_horizontal();
+        assert_eq!(flipped.to_string(), "cba\nfed\n");
+
+        flipped.flip_vertical();
+        assert_eq!(flipped.to_string(), "fed\ncba\n");
+        assert_eq!(flipped.rotate_clockwise().rotate_clockwise(), grid);
+    }
+}
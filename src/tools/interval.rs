@@ -0,0 +1,421 @@
+//! Half-open intervals and sets of them, for puzzles that work with large
+//! ranges of numbers that can't be enumerated one by one.
+
+use std::{
+    fmt::Display,
+    ops::{Add, Range, Sub},
+};
+
+/// The half-open interval `start..end`, which is empty if `start >= end`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+/// A set of numbers stored as sorted, disjoint and non-adjacent intervals,
+/// none of which are empty. This normal form makes equal sets compare equal.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+/// A piecewise function that adds a different offset to each of its disjoint
+/// intervals, and leaves other numbers unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalMap<T> {
+    pieces: Vec<(Interval<T>, T)>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<T: Copy + Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        self.start <= x && x < self.end
+    }
+
+    /// Whether the intervals have at least one number in common.
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Returns the numbers that are in both intervals, which may be empty.
+    pub fn intersection(&self, other: &Interval<T>) -> Interval<T> {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Splits the interval into the numbers below `at` and the others, either
+    /// of which may be empty.
+    pub fn split_at(&self, at: T) -> (Interval<T>, Interval<T>) {
+        let at = at.clamp(self.start, self.end.max(self.start));
+        (Interval::new(self.start, at), Interval::new(at, self.end))
+    }
+}
+
+impl<T: Copy + Ord + Sub<Output = T> + Default> Interval<T> {
+    /// Returns the number of integers in the interval.
+    pub fn len(&self) -> T {
+        match self.is_empty() {
+            true => T::default(),
+            false => self.end - self.start,
+        }
+    }
+}
+
+impl<T: Copy + Add<Output = T>> Interval<T> {
+    /// Returns the interval shifted by the given offset.
+    pub fn offset(&self, offset: T) -> Interval<T> {
+        Interval {
+            start: self.start + offset,
+            end: self.end + offset,
+        }
+    }
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Returns the intervals of the set, in increasing order.
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn contains(&self, x: T) -> bool {
+        let index = self.intervals.partition_point(|i| i.end <= x);
+        self.intervals.get(index).is_some_and(|i| i.contains(x))
+    }
+
+    /// Returns the smallest number of the set.
+    pub fn min(&self) -> Option<T> {
+        self.intervals.first().map(|i| i.start)
+    }
+
+    /// Returns the end of the last interval, which is not part of the set.
+    pub fn max_end(&self) -> Option<T> {
+        self.intervals.last().map(|i| i.end)
+    }
+
+    pub fn insert(&mut self, interval: Interval<T>) {
+        *self = self.union(&IntervalSet::from(interval));
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut merged = self.intervals.clone();
+        merged.extend_from_slice(&other.intervals);
+        merged.into_iter().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let (mut a, mut b) = (self.intervals.iter(), other.intervals.iter());
+        let (mut x, mut y) = (a.next(), b.next());
+        let mut intervals = Vec::new();
+
+        while let (Some(i), Some(j)) = (x, y) {
+            let common = i.intersection(j);
+
+            if !common.is_empty() {
+                intervals.push(common);
+            }
+
+            // Advance the interval that ends first, as it can't overlap anything else
+            match i.end <= j.end {
+                true => x = a.next(),
+                false => y = b.next(),
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// Returns the numbers of this set that are not in the other.
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut intervals = Vec::new();
+        let mut others = other.intervals.iter().peekable();
+
+        for &interval in &self.intervals {
+            let mut rest = interval;
+
+            // Skip intervals that are entirely before this one
+            while others.next_if(|o| o.end <= rest.start).is_some() {}
+
+            while let Some(o) = others.peek() {
+                if o.start >= rest.end {
+                    break;
+                }
+
+                let (before, _) = rest.split_at(o.start);
+                let (_, after) = rest.split_at(o.end);
+
+                if !before.is_empty() {
+                    intervals.push(before);
+                }
+
+                rest = after;
+
+                // The other interval may also cut the next interval of this set
+                if o.end > interval.end {
+                    break;
+                }
+
+                others.next();
+            }
+
+            if !rest.is_empty() {
+                intervals.push(rest);
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// Splits the set into the numbers below `at` and the others.
+    pub fn split_at(&self, at: T) -> (IntervalSet<T>, IntervalSet<T>) {
+        let (mut below, mut above) = (Vec::new(), Vec::new());
+
+        for interval in &self.intervals {
+            let (low, high) = interval.split_at(at);
+
+            if !low.is_empty() {
+                below.push(low);
+            }
+
+            if !high.is_empty() {
+                above.push(high);
+            }
+        }
+
+        (
+            IntervalSet { intervals: below },
+            IntervalSet { intervals: above },
+        )
+    }
+}
+
+impl<T: Copy + Ord + Sub<Output = T> + Add<Output = T> + Default> IntervalSet<T> {
+    /// Returns the number of integers in the set.
+    pub fn len(&self) -> T {
+        self.intervals
+            .iter()
+            .fold(T::default(), |total, i| total + i.len())
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T>> IntervalMap<T> {
+    pub fn new() -> Self {
+        Self { pieces: Vec::new() }
+    }
+
+    /// Adds the offset to the numbers of the source interval. Returns false and
+    /// leaves the map unchanged if the source overlaps an existing piece.
+    pub fn insert(&mut self, source: Interval<T>, offset: T) -> bool {
+        if source.is_empty() {
+            return true;
+        }
+
+        let index = self.pieces.partition_point(|(i, _)| i.start < source.start);
+        let overlaps_previous = index > 0 && self.pieces[index - 1].0.end > source.start;
+        let overlaps_next = self
+            .pieces
+            .get(index)
+            .is_some_and(|(i, _)| i.start < source.end);
+
+        if overlaps_previous || overlaps_next {
+            return false;
+        }
+
+        self.pieces.insert(index, (source, offset));
+        true
+    }
+
+    /// Maps a single number.
+    pub fn map(&self, x: T) -> T {
+        let index = self.pieces.partition_point(|(i, _)| i.end <= x);
+
+        match self.pieces.get(index) {
+            Some(&(interval, offset)) if interval.contains(x) => x + offset,
+            _ => x,
+        }
+    }
+
+    /// Maps every number of the set, splitting intervals that straddle pieces.
+    pub fn map_set(&self, set: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut mapped = Vec::new();
+
+        for &interval in set.intervals() {
+            let mut rest = interval;
+
+            let first = self.pieces.partition_point(|(i, _)| i.end <= rest.start);
+
+            for &(piece, offset) in &self.pieces[first..] {
+                if piece.start >= rest.end {
+                    break;
+                }
+
+                let (gap, after) = rest.split_at(piece.start);
+                let (inside, after) = after.split_at(piece.end);
+
+                mapped.push(gap);
+                mapped.push(inside.offset(offset));
+                rest = after;
+            }
+
+            mapped.push(rest);
+        }
+
+        mapped.into_iter().collect()
+    }
+}
+
+impl<T: Copy + Ord> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Ord + Add<Output = T>> Default for IntervalMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Normalises any intervals into a set, dropping empty ones and merging those
+/// that overlap or touch.
+impl<T: Copy + Ord> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut sorted: Vec<_> = iter.into_iter().filter(|i| !i.is_empty()).collect();
+        sorted.sort_unstable();
+
+        let mut intervals: Vec<Interval<T>> = Vec::with_capacity(sorted.len());
+
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+}
+
+impl<T: Copy + Ord> From<Interval<T>> for IntervalSet<T> {
+    fn from(interval: Interval<T>) -> Self {
+        IntervalSet::from_iter([interval])
+    }
+}
+
+impl<T> From<Range<T>> for Interval<T> {
+    fn from(range: Range<T>) -> Self {
+        Interval {
+            start: range.start,
+            end: range.end,
+        }
+    }
+}
+
+impl<T> From<Interval<T>> for Range<T> {
+    fn from(interval: Interval<T>) -> Self {
+        interval.start..interval.end
+    }
+}
+
+impl<T: Display> Display for Interval<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(ranges: &[(i32, i32)]) -> IntervalSet<i32> {
+        ranges
+            .iter()
+            .map(|&(start, end)| Interval::new(start, end))
+            .collect()
+    }
+
+    #[test]
+    fn test_interval() {
+        let interval = Interval::new(2, 6);
+
+        assert_eq!(interval.len(), 4);
+        assert!(interval.contains(2) && !interval.contains(6));
+        assert_eq!(
+            interval.split_at(4),
+            (Interval::new(2, 4), Interval::new(4, 6))
+        );
+        assert_eq!(interval.split_at(0), (Interval::new(2, 2), interval));
+        assert!(interval.split_at(9).1.is_empty());
+        assert!(!interval.overlaps(&Interval::new(6, 8)));
+        assert_eq!(Interval::new(5, 1).len(), 0);
+    }
+
+    #[test]
+    fn test_normalisation() {
+        let normalised = set(&[(5, 7), (0, 2), (1, 3), (3, 4), (8, 8)]);
+
+        assert_eq!(
+            normalised.intervals(),
+            [Interval::new(0, 4), Interval::new(5, 7)]
+        );
+        assert_eq!(normalised.len(), 6);
+        assert!(normalised.contains(3) && !normalised.contains(4));
+        assert_eq!(normalised.min(), Some(0));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = set(&[(0, 5), (10, 15)]);
+        let b = set(&[(3, 12), (14, 20)]);
+
+        assert_eq!(a.union(&b), set(&[(0, 20)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 5), (10, 12), (14, 15)]));
+        assert_eq!(a.difference(&b), set(&[(0, 3), (12, 14)]));
+        assert_eq!(b.difference(&a), set(&[(5, 10), (15, 20)]));
+        assert_eq!(a.difference(&set(&[(-5, 30)])), set(&[]));
+        assert_eq!(
+            a.difference(&set(&[(1, 2), (3, 4)])),
+            set(&[(0, 1), (2, 3), (4, 5), (10, 15)])
+        );
+        assert_eq!(a.split_at(12), (set(&[(0, 5), (10, 12)]), set(&[(12, 15)])));
+
+        let mut c = a.clone();
+        c.insert(Interval::new(5, 10));
+        assert_eq!(c, set(&[(0, 15)]));
+    }
+
+    #[test]
+    fn test_map() {
+        let mut map = IntervalMap::new();
+
+        assert!(map.insert(Interval::new(98, 100), -48));
+        assert!(map.insert(Interval::new(50, 98), 2));
+        assert!(!map.insert(Interval::new(60, 70), 1));
+
+        assert_eq!(map.map(79), 81);
+        assert_eq!(map.map(99), 51);
+        assert_eq!(map.map(13), 13);
+
+        let seeds = set(&[(79, 93), (55, 68), (95, 105)]);
+        let expected = set(&[(81, 95), (57, 70), (97, 100), (50, 52), (100, 105)]);
+        assert_eq!(map.map_set(&seeds), expected);
+    }
+}
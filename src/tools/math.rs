@@ -0,0 +1,293 @@
+//! Number theory helpers. Functions that can overflow return [`None`] instead
+//! of panicking or silently wrapping.
+
+use std::ops::Rem;
+
+/// A congruence `x ≡ residue (mod modulus)`, with `0 <= residue < modulus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Congruence {
+    pub residue: i64,
+    pub modulus: i64,
+}
+
+/// An unsigned integer, where [`Default`] is zero.
+pub trait Unsigned: Copy + Eq + Rem<Output = Self> + Default {}
+
+impl Unsigned for u32 {}
+impl Unsigned for u64 {}
+impl Unsigned for u128 {}
+impl Unsigned for usize {}
+
+/// Returns the greatest common divisor, where `gcd(0, n) = n`.
+pub fn gcd<T: Unsigned>(a: T, b: T) -> T {
+    let (mut a, mut b) = (a, b);
+
+    while b != T::default() {
+        (a, b) = (b, a % b);
+    }
+
+    a
+}
+
+/// Returns the least common multiple, where `lcm(0, n) = 0`, or [`None`] if it
+/// does not fit in a [`u64`].
+pub fn lcm(a: u64, b: u64) -> Option<u64> {
+    match gcd(a, b) {
+        0 => Some(0),
+        divisor => (a / divisor).checked_mul(b),
+    }
+}
+
+/// Returns the greatest common divisor of all numbers, which is 0 if there are none.
+pub fn gcd_all<T: Unsigned>(numbers: impl IntoIterator<Item = T>) -> T {
+    numbers.into_iter().fold(T::default(), gcd)
+}
+
+/// Returns the least common multiple of all numbers, which is 1 if there are none,
+/// or [`None`] if it does not fit in a [`u64`].
+pub fn lcm_all(numbers: impl IntoIterator<Item = u64>) -> Option<u64> {
+    numbers.into_iter().try_fold(1, lcm)
+}
+
+/// Returns `(g, x, y)` such that `a * x + b * y = g`, where `g = gcd(a, b) >= 0`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (mut r0, mut r1) = (a as i128, b as i128);
+    let (mut x0, mut x1) = (1_i128, 0);
+    let (mut y0, mut y1) = (0_i128, 1);
+
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (x0, x1) = (x1, x0 - q * x1);
+        (y0, y1) = (y1, y0 - q * y1);
+    }
+
+    if r0 < 0 {
+        (r0, x0, y0) = (-r0, -x0, -y0);
+    }
+
+    // The Bézout coefficients are bounded by |a| and |b|, except for i64::MIN
+    (r0 as i64, x0 as i64, y0 as i64)
+}
+
+/// Returns the inverse of `a` modulo `m` in `0..m`, if `a` and `m` are coprime.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    assert!(m > 0, "modulus must be positive");
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Returns `base` raised to the power `exp`, modulo `m`.
+pub fn mod_pow(base: u64, exp: u64, m: u64) -> u64 {
+    assert!(m > 0, "modulus must be positive");
+
+    let m = m as u128;
+    let mut base = base as u128 % m;
+    let mut exp = exp;
+    let mut result = 1 % m;
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+
+        base = base * base % m;
+        exp >>= 1;
+    }
+
+    result as u64
+}
+
+/// Returns the integer square root, the largest `r` such that `r * r <= n`.
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+
+    // Newton's method from an overestimate decreases until it reaches the root
+    let mut x = 1_u128 << (n.ilog2() / 2 + 1);
+
+    loop {
+        let next = (x + n / x) / 2;
+
+        if next >= x {
+            return x;
+        }
+
+        x = next;
+    }
+}
+
+/// Evaluates at `x` the polynomial of lowest degree that takes the values of
+/// the sequence at `0, 1, 2, ...`, using Newton's forward differences. At the
+/// length of the sequence, this predicts its next value. Returns [`None`] if
+/// the result does not fit in an [`i64`].
+pub fn extrapolate(sequence: &[i64], x: i64) -> Option<i64> {
+    let mut differences = sequence.to_vec();
+    let mut binomial = 1_i128;
+    let mut result = 0_i128;
+
+    // Sums the binomial(x, k) multiples of the k-th differences at 0, until
+    // the differences are all zero
+    for k in 0..sequence.len() as i128 {
+        if differences.iter().all(|&d| d == 0) {
+            break;
+        }
+
+        result = result.checked_add(binomial.checked_mul(differences[0] as i128)?)?;
+        binomial = binomial.checked_mul(x as i128 - k)? / (k + 1);
+
+        for i in 0..differences.len() - 1 {
+            differences[i] = differences[i + 1].checked_sub(differences[i])?;
+        }
+
+        differences.pop();
+    }
+
+    result.try_into().ok()
+}
+
+/// Solves a system of congruences with the Chinese remainder theorem. The moduli
+/// do not need to be coprime. Returns [`None`] if the system has no solution, or
+/// if the combined modulus does not fit in an [`i64`].
+pub fn crt(congruences: impl IntoIterator<Item = Congruence>) -> Option<Congruence> {
+    congruences
+        .into_iter()
+        .try_fold(Congruence::new(0, 1), Congruence::combine)
+}
+
+impl Congruence {
+    /// Creates the congruence `x ≡ residue (mod modulus)`, normalising the residue.
+    ///
+    /// # Panics
+    /// If the modulus is not positive.
+    pub fn new(residue: i64, modulus: i64) -> Self {
+        assert!(modulus > 0, "modulus must be positive");
+
+        Self {
+            residue: residue.rem_euclid(modulus),
+            modulus,
+        }
+    }
+
+    /// Whether `x` is a solution of the congruence.
+    pub fn contains(&self, x: i64) -> bool {
+        x.rem_euclid(self.modulus) == self.residue
+    }
+
+    /// Returns the smallest solution that is greater than or equal to `min`,
+    /// which is useful for congruences that only hold after an offset.
+    pub fn first_at_least(&self, min: i64) -> i64 {
+        min + (self.residue - min).rem_euclid(self.modulus)
+    }
+
+    /// Returns the congruence that is satisfied by the solutions of both, if
+    /// there are any.
+    pub fn combine(self, other: Congruence) -> Option<Congruence> {
+        let (g, p, _) = extended_gcd(self.modulus, other.modulus);
+        let difference = other.residue - self.residue;
+
+        if difference % g != 0 {
+            return None;
+        }
+
+        // x = r1 + m1 * k, where m1 * k ≡ r2 - r1 (mod m2)
+        let step = other.modulus / g;
+        let modulus = self.modulus.checked_mul(step)?;
+        let k = (difference / g) as i128 * p as i128 % step as i128;
+        let residue = self.residue as i128 + self.modulus as i128 * k;
+
+        Some(Congruence::new(
+            residue.rem_euclid(modulus as i128) as i64,
+            modulus,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12_u64, 18), 6);
+        assert_eq!(gcd(0_u64, 7), 7);
+        assert_eq!(gcd(7_u32, 0), 7);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(u64::MAX, 2), None);
+        assert_eq!(gcd_all([12_u64, 18, 27]), 3);
+        assert_eq!(gcd_all([1_u128 << 100, 3 << 90]), 1 << 90);
+        assert_eq!(lcm_all([2, 3, 4, 5]), Some(60));
+        assert_eq!(lcm_all([]), Some(1));
+    }
+
+    #[test]
+    fn test_extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (0, 5), (17, -5), (0, 0)] {
+            let (g, x, y) = extended_gcd(a, b);
+            assert_eq!(g, gcd(a.unsigned_abs(), b.unsigned_abs()) as i64);
+            assert_eq!(a * x + b * y, g);
+        }
+    }
+
+    #[test]
+    fn test_modular() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(6, 9), None);
+        assert_eq!(mod_pow(4, 13, 497), 445);
+        assert_eq!(mod_pow(u64::MAX, u64::MAX, 1_000_000_007), 254_368_884);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_isqrt() {
+        for n in 0..1000 {
+            let r = isqrt(n);
+            assert!(r * r <= n && (r + 1) * (r + 1) > n);
+        }
+
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+        assert_eq!(isqrt((1 << 100) - 1), (1 << 50) - 1);
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        assert_eq!(extrapolate(&[0, 3, 6, 9, 12, 15], 6), Some(18));
+        assert_eq!(extrapolate(&[10, 13, 16, 21, 30, 45], 6), Some(68));
+        assert_eq!(extrapolate(&[10, 13, 16, 21, 30, 45], -1), Some(5));
+        assert_eq!(
+            extrapolate(&[1, 4, 9], 1_000_000),
+            Some(1_000_001 * 1_000_001)
+        );
+        assert_eq!(extrapolate(&[7], 42), Some(7));
+        assert_eq!(extrapolate(&[], 3), Some(0));
+        assert_eq!(extrapolate(&[0, 1, 4], 1 << 40), None);
+    }
+
+    #[test]
+    fn test_crt() {
+        let c = Congruence::new;
+
+        assert_eq!(crt([c(2, 3), c(3, 5), c(2, 7)]), Some(c(23, 105)));
+        assert_eq!(crt([c(3, 4), c(1, 6)]), Some(c(7, 12)));
+        assert_eq!(crt([c(0, 4), c(1, 6)]), None);
+        assert_eq!(crt([c(-1, 4)]), Some(c(3, 4)));
+        assert_eq!(crt([]), Some(c(0, 1)));
+
+        let large = i64::MAX / 2;
+        assert_eq!(crt([c(0, large), c(0, large - 2)]), None);
+    }
+
+    #[test]
+    fn test_first_at_least() {
+        let congruence = Congruence::new(3, 7);
+
+        assert_eq!(congruence.first_at_least(0), 3);
+        assert_eq!(congruence.first_at_least(4), 10);
+        assert_eq!(congruence.first_at_least(10), 10);
+        assert_eq!(congruence.first_at_least(-5), -4);
+        assert!(congruence.contains(-4));
+    }
+}
@@ -0,0 +1,256 @@
+//! Mazes on a grid, compressed into a small graph of junctions where each edge
+//! is a corridor, so that searches only branch where the maze does.
+//!
+//! The longest simple path between two junctions is NP-hard in general, but
+//! puzzle mazes have few enough junctions for a depth-first search that keeps
+//! the visited junctions in a bitmask.
+
+use std::collections::HashMap;
+
+use super::{grid::Grid, Direction, UCoords};
+
+/// The junctions of a maze and the corridors between them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JunctionGraph {
+    junctions: Vec<UCoords>,
+    indices: HashMap<UCoords, usize>,
+    /// The corridors leaving each junction, as the junction they lead to and
+    /// their length in steps.
+    edges: Vec<Vec<(usize, u32)>>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl JunctionGraph {
+    /// The largest number of junctions supported by [`longest_path`].
+    ///
+    /// [`longest_path`]: JunctionGraph::longest_path
+    pub const MAX_JUNCTIONS: usize = u64::BITS as usize;
+
+    /// Compresses a maze. The `can_move` function tells if one can step from a
+    /// cell in a direction, and is only called when the target is within the
+    /// grid. Junctions are the cells connected to at least three others,
+    /// along with the given points, such as the entrance and the exit.
+    ///
+    /// Moves may be one-way, such as down slopes, in which case a corridor is
+    /// only an edge in the direction it can be walked. Dead ends are dropped.
+    pub fn compress<T>(
+        grid: &Grid<T>,
+        points: impl IntoIterator<Item = UCoords>,
+        can_move: impl Fn(UCoords, Direction) -> bool,
+    ) -> Self {
+        let connected = |at: UCoords, direction: Direction| {
+            grid.step(at, direction)
+                .is_some_and(|next| can_move(at, direction) || can_move(next, direction.reverse()))
+        };
+
+        let mut junctions: Vec<UCoords> = points.into_iter().collect();
+        let branches: Vec<_> = (grid.iter().map(|(at, _)| at))
+            .filter(|at| !junctions.contains(at))
+            .filter(|&at| {
+                Direction::ALL
+                    .into_iter()
+                    .filter(|&direction| connected(at, direction))
+                    .count()
+                    >= 3
+            })
+            .collect();
+        junctions.extend(branches);
+
+        let indices: HashMap<_, _> = junctions
+            .iter()
+            .enumerate()
+            .map(|(index, &at)| (at, index))
+            .collect();
+
+        let edges = junctions
+            .iter()
+            .map(|&start| {
+                Direction::ALL
+                    .into_iter()
+                    .filter_map(|direction| {
+                        Self::follow(grid, &indices, start, direction, &can_move)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self {
+            junctions,
+            indices,
+            edges,
+        }
+    }
+
+    /// Walks a corridor from a junction until the next junction, returning it
+    /// with the length of the corridor.
+    fn follow<T>(
+        grid: &Grid<T>,
+        indices: &HashMap<UCoords, usize>,
+        start: UCoords,
+        mut direction: Direction,
+        can_move: impl Fn(UCoords, Direction) -> bool,
+    ) -> Option<(usize, u32)> {
+        let mut at = start;
+        let mut length = 0;
+
+        loop {
+            at = grid
+                .step(at, direction)
+                .filter(|_| can_move(at, direction))?;
+            length += 1;
+
+            if let Some(&index) = indices.get(&at) {
+                return Some((index, length));
+            }
+
+            // Corridors without junctions can loop forever
+            if length as usize > grid.cells().len() {
+                return None;
+            }
+
+            direction = [direction, direction.turn_left(), direction.turn_right()]
+                .into_iter()
+                .find(|&next| grid.step(at, next).is_some() && can_move(at, next))?;
+        }
+    }
+
+    /// Returns the number of junctions.
+    pub fn len(&self) -> usize {
+        self.junctions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.junctions.is_empty()
+    }
+
+    /// Returns the position of a junction.
+    pub fn junction(&self, index: usize) -> UCoords {
+        self.junctions[index]
+    }
+
+    /// Returns the junction at a position.
+    pub fn index(&self, at: UCoords) -> Option<usize> {
+        self.indices.get(&at).copied()
+    }
+
+    /// Returns the corridors leaving a junction, as the junction they lead to
+    /// and their length.
+    pub fn edges(&self, index: usize) -> &[(usize, u32)] {
+        &self.edges[index]
+    }
+
+    /// Returns the length of the longest path from one junction to another
+    /// that never visits a junction twice, or [`None`] if there is no path.
+    ///
+    /// # Panics
+    /// If there are more than [`JunctionGraph::MAX_JUNCTIONS`] junctions.
+    pub fn longest_path(&self, from: usize, to: usize) -> Option<u32> {
+        assert!(self.len() <= Self::MAX_JUNCTIONS, "too many junctions");
+
+        // A path can enter each junction at most once, through its longest
+        // incoming corridor at best, which bounds what is left to gain
+        let mut longest_entry = vec![0; self.len()];
+
+        for &(next, length) in self.edges.iter().flatten() {
+            longest_entry[next] = longest_entry[next].max(length);
+        }
+
+        let mut search = LongestPath {
+            graph: self,
+            longest_entry: &longest_entry,
+            to,
+            best: None,
+        };
+
+        let remaining = longest_entry.iter().sum::<u32>() - longest_entry[from];
+        search.visit(from, 1 << from, 0, remaining);
+        search.best
+    }
+}
+
+/// The state of the depth-first search of [`JunctionGraph::longest_path`].
+struct LongestPath<'a> {
+    graph: &'a JunctionGraph,
+    longest_entry: &'a [u32],
+    to: usize,
+    best: Option<u32>,
+}
+
+impl LongestPath<'_> {
+    fn visit(&mut self, at: usize, visited: u64, length: u32, remaining: u32) {
+        if at == self.to {
+            self.best = self.best.max(Some(length));
+            return;
+        }
+
+        if self.best.is_some_and(|best| length + remaining <= best) {
+            return;
+        }
+
+        for &(next, corridor) in &self.graph.edges[at] {
+            if visited & (1 << next) == 0 {
+                let remaining = remaining - self.longest_entry[next];
+                self.visit(next, visited | 1 << next, length + corridor, remaining);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAZE: &str = "\
+#.#######
+#...>...#
+#.#.###.#
+#.#...#.#
+#.###.#.#
+#.......#
+#######.#
+";
+
+    fn compress(slopes: bool) -> (JunctionGraph, usize, usize) {
+        let grid = Grid::<u8>::parse_with(MAZE, "a tile", Some).unwrap();
+        let (start, end) = (UCoords::new(1, 0), UCoords::new(7, 6));
+
+        let graph = JunctionGraph::compress(&grid, [start, end], |at, direction| {
+            let next = grid.step(at, direction).unwrap();
+            match (grid[at], grid[next]) {
+                (b'#', _) | (_, b'#') => false,
+                (b'>', _) if slopes => direction == Direction::East,
+                _ => true,
+            }
+        });
+
+        let (from, to) = (graph.index(start).unwrap(), graph.index(end).unwrap());
+        (graph, from, to)
+    }
+
+    #[test]
+    fn test_compress() {
+        let (graph, from, _) = compress(false);
+
+        // The entrance, the exit and the junctions at (1,1), (3,1), (5,5), (7,5)
+        assert_eq!(graph.len(), 6);
+        assert_eq!(graph.edges(from), [(2, 1)]);
+
+        let top_left = graph.index(UCoords::new(1, 1)).unwrap();
+        let mut lengths: Vec<_> = graph.edges(top_left).iter().map(|e| e.1).collect();
+        lengths.sort();
+        assert_eq!(lengths, [1, 2, 8]);
+    }
+
+    #[test]
+    fn test_longest_path() {
+        let (graph, from, to) = compress(false);
+        assert_eq!(graph.longest_path(from, to), Some(24));
+        assert_eq!(graph.longest_path(to, from), Some(24));
+
+        // The slope can only be taken eastwards
+        let (graph, from, to) = compress(true);
+        assert_eq!(graph.longest_path(from, to), Some(24));
+        assert_eq!(graph.longest_path(to, from), Some(12));
+    }
+}
@@ -1,9 +1,30 @@
 pub mod algorithms;
 pub mod atom;
+mod coords3;
+pub mod cycle;
+pub mod direction;
+pub mod dsu;
+pub mod geometry;
+pub mod graph;
+pub mod grid;
+pub mod interval;
+pub mod math;
+pub mod maze;
+pub mod nonogram;
+pub mod parse;
+pub mod rational;
+pub mod search;
+mod vector;
+
+pub use coords3::{Coords3, UCoords3};
+pub use direction::{Direction, Direction8, Turn};
+pub use grid::Grid;
+pub use vector::{BoundingBox, Vector};
 
 use std::{
     fmt::Display,
-    ops::{Add, AddAssign, Mul, Sub},
+    num::TryFromIntError,
+    ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign},
 };
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -27,6 +48,12 @@ impl Coords {
         self.x.unsigned_abs() + self.y.unsigned_abs()
     }
 
+    /// Returns the z component of the 3D cross product, which is positive if
+    /// `rhs` is clockwise from `self` with the y axis pointing down.
+    pub fn cross(self, rhs: Coords) -> i64 {
+        self.x * rhs.y - self.y * rhs.x
+    }
+
     pub fn ucoords(&self, bounds: &UCoords) -> Option<UCoords> {
         let x: usize = self.x.try_into().ok()?;
         let y: usize = self.y.try_into().ok()?;
@@ -62,6 +89,23 @@ impl Sub for Coords {
     }
 }
 
+impl SubAssign for Coords {
+    fn sub_assign(&mut self, rhs: Coords) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Coords {
+    type Output = Coords;
+
+    fn neg(self) -> Self::Output {
+        Coords {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
 impl Mul<i64> for Coords {
     type Output = Coords;
 
@@ -73,6 +117,18 @@ impl Mul<i64> for Coords {
     }
 }
 
+/// Divides each component, rounding towards zero.
+impl Div<i64> for Coords {
+    type Output = Coords;
+
+    fn div(self, rhs: i64) -> Self::Output {
+        Coords {
+            x: self.x / rhs,
+            y: self.y / rhs,
+        }
+    }
+}
+
 impl Display for Coords {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "({},{})", self.x, self.y)
@@ -103,11 +159,29 @@ impl From<(usize, usize)> for UCoords {
     }
 }
 
+/// # Panics
+/// If a component does not fit in an [`i64`], which can't happen for the
+/// positions of an in-memory grid.
 impl From<UCoords> for Coords {
     fn from(val: UCoords) -> Coords {
+        let component = |n: usize| i64::try_from(n).expect("coordinate out of range");
+
         Coords {
-            x: val.x as i64,
-            y: val.y as i64,
+            x: component(val.x),
+            y: component(val.y),
         }
     }
 }
+
+/// Fails if any component is negative, unlike [`Coords::ucoords`] which also
+/// checks bounds.
+impl TryFrom<Coords> for UCoords {
+    type Error = TryFromIntError;
+
+    fn try_from(val: Coords) -> Result<UCoords, Self::Error> {
+        Ok(UCoords {
+            x: val.x.try_into()?,
+            y: val.y.try_into()?,
+        })
+    }
+}
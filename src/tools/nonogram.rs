@@ -0,0 +1,267 @@
+//! Rows of nonograms, where some cells are known to be filled or empty and
+//! clues give the lengths of the groups of filled cells, in order.
+//!
+//! A [`Row`] counts the ways to complete the unknown cells with dynamic
+//! programming over the cells and the clues, which also allows enumerating the
+//! arrangements, or picking one of them directly by its rank.
+//!
+//! ```
+//! use advent_of_code::tools::nonogram::{Cell, Row};
+//!
+//! let cells: Vec<Cell> = "???.###".bytes().map(|b| b.try_into().unwrap()).collect();
+//!
+//! let row = Row::new(&cells, &[1, 1, 3], 1);
+//! assert_eq!(row.count(), 1);
+//! assert_eq!(Row::render(&row.nth(0).unwrap()), "#.#.###");
+//!
+//! // Repeated five times, with unknown cells in between
+//! assert_eq!(Row::new(&cells, &[1, 1, 3], 5).count(), 1);
+//! ```
+
+use std::iter;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Cell {
+    Empty,
+    Filled,
+    Unknown,
+}
+
+/// A row of cells with the lengths of its groups of filled cells.
+#[derive(Clone, Debug)]
+pub struct Row {
+    cells: Vec<Cell>,
+    clues: Vec<usize>,
+    /// The number of ways to complete the cells from `i` onwards with the
+    /// clues from `g` onwards, at index `i * (clues + 1) + g`.
+    ways: Vec<u64>,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl Row {
+    /// Creates a row from its cells and clues, both repeated `scale` times,
+    /// with an unknown cell between each copy of the cells.
+    ///
+    /// # Panics
+    /// If a clue is zero, or if the number of arrangements overflows a [`u64`].
+    pub fn new(cells: &[Cell], clues: &[usize], scale: usize) -> Self {
+        assert!(
+            clues.iter().all(|&length| length > 0),
+            "clues must not be zero"
+        );
+
+        let cells: Vec<Cell> = (0..scale)
+            .flat_map(|copy| {
+                let separator = (copy > 0).then_some(Cell::Unknown);
+                separator.into_iter().chain(cells.iter().copied())
+            })
+            .collect();
+
+        let clues = clues.repeat(scale);
+        let ways = Self::ways(&cells, &clues);
+
+        Row { cells, clues, ways }
+    }
+
+    /// Counts the completions of every suffix of the cells by every suffix
+    /// of the clues, from the end of the row. A suffix either starts with an
+    /// empty cell, or with its first group followed by an empty cell.
+    fn ways(cells: &[Cell], clues: &[usize]) -> Vec<u64> {
+        let stride = clues.len() + 1;
+        let mut ways: Vec<u64> = vec![0; (cells.len() + 1) * stride];
+        ways[cells.len() * stride + clues.len()] = 1;
+
+        // The length of the run of cells that can be filled from each cell
+        let mut fillable = vec![0; cells.len() + 1];
+
+        for i in (0..cells.len()).rev() {
+            if cells[i] != Cell::Empty {
+                fillable[i] = fillable[i + 1] + 1;
+            }
+
+            for (g, &length) in clues.iter().enumerate().rev() {
+                let end = i + length;
+
+                if fillable[i] >= length && cells.get(end) != Some(&Cell::Filled) {
+                    let next = (end + 1).min(cells.len());
+                    ways[i * stride + g] = ways[i * stride + g]
+                        .checked_add(ways[next * stride + g + 1])
+                        .expect("too many arrangements");
+                }
+            }
+
+            if cells[i] != Cell::Filled {
+                for g in 0..stride {
+                    ways[i * stride + g] = ways[i * stride + g]
+                        .checked_add(ways[(i + 1) * stride + g])
+                        .expect("too many arrangements");
+                }
+            }
+        }
+
+        ways
+    }
+
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn clues(&self) -> &[usize] {
+        &self.clues
+    }
+
+    /// Returns the number of ways to complete the row.
+    pub fn count(&self) -> u64 {
+        self.ways[0]
+    }
+
+    /// Returns the arrangement at an index, in the order where empty cells
+    /// come before filled ones, with `true` for filled cells. Returns
+    /// [`None`] if there are not that many arrangements.
+    pub fn nth(&self, mut index: u64) -> Option<Vec<bool>> {
+        if index >= self.count() {
+            return None;
+        }
+
+        let stride = self.clues.len() + 1;
+        let (mut i, mut g) = (0, 0);
+        let mut arrangement = Vec::with_capacity(self.cells.len());
+
+        while i < self.cells.len() {
+            let empty = match self.cells[i] {
+                Cell::Filled => 0,
+                _ => self.ways[(i + 1) * stride + g],
+            };
+
+            if index < empty {
+                arrangement.push(false);
+                i += 1;
+                continue;
+            }
+
+            // The rest of the arrangements start with the next group
+            index -= empty;
+            let end = i + self.clues[g];
+            arrangement.extend(iter::repeat(true).take(self.clues[g]));
+
+            if end < self.cells.len() {
+                arrangement.push(false);
+            }
+
+            (i, g) = ((end + 1).min(self.cells.len()), g + 1);
+        }
+
+        Some(arrangement)
+    }
+
+    /// Iterates over every arrangement, in the order of [`Row::nth`].
+    pub fn arrangements(&self) -> impl Iterator<Item = Vec<bool>> + '_ {
+        (0..self.count()).map(|index| self.nth(index).unwrap())
+    }
+
+    /// Picks the arrangement at `random` modulo the number of arrangements,
+    /// which is uniform for a `random` number uniform over a range much
+    /// larger than that. Returns [`None`] if the row has no arrangement.
+    pub fn sample(&self, random: u64) -> Option<Vec<bool>> {
+        self.nth(random.checked_rem(self.count())?)
+    }
+
+    /// Formats an arrangement with `#` for filled cells and `.` for empty ones.
+    pub fn render(arrangement: &[bool]) -> String {
+        let cell = |&filled: &bool| if filled { '#' } else { '.' };
+        arrangement.iter().map(cell).collect()
+    }
+}
+
+impl TryFrom<u8> for Cell {
+    type Error = ();
+
+    /// Reads a cell, with `.` for empty cells, `#` for filled ones and `?` for
+    /// unknown ones.
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'.' => Ok(Cell::Empty),
+            b'#' => Ok(Cell::Filled),
+            b'?' => Ok(Cell::Unknown),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const E: Cell = Cell::Empty;
+    const F: Cell = Cell::Filled;
+    const U: Cell = Cell::Unknown;
+
+    fn row(cells: &str, clues: &[usize], scale: usize) -> Row {
+        let cells: Vec<Cell> = cells.bytes().map(|b| b.try_into().unwrap()).collect();
+        Row::new(&cells, clues, scale)
+    }
+
+    #[test]
+    fn test_count() {
+        let cells = [E, U, U, E, E, U, U, E, E, E, U, F, F, E];
+        assert_eq!(Row::new(&cells, &[1, 1, 3], 1).count(), 4);
+
+        let cells = [U, F, F, F, U, U, U, U, U, U, U, U];
+        assert_eq!(Row::new(&cells, &[3, 2, 1], 1).count(), 10);
+
+        assert_eq!(row("", &[], 1).count(), 1);
+        assert_eq!(row("???", &[], 1).count(), 1);
+        assert_eq!(row("?#?", &[], 1).count(), 0);
+        assert_eq!(row("??", &[3], 1).count(), 0);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!(row(".??..??...?##.", &[1, 1, 3], 5).count(), 16384);
+        assert_eq!(row("?###????????", &[3, 2, 1], 5).count(), 506250);
+
+        let scaled = row("#?", &[1], 3);
+        assert_eq!(scaled.cells(), [F, U, U, F, U, U, F, U]);
+        assert_eq!(scaled.clues(), [1, 1, 1]);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let row = row("?###????????", &[3, 2, 1], 1);
+        let arrangements: Vec<_> = row.arrangements().collect();
+
+        assert_eq!(arrangements.len(), 10);
+        assert_eq!(Row::render(&arrangements[0]), ".###....##.#");
+        assert_eq!(Row::render(&arrangements[9]), ".###.##.#...");
+        assert!(arrangements.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(row.nth(10), None);
+    }
+
+    #[test]
+    fn test_sample() {
+        let row = row("??.??", &[1, 1], 1);
+
+        for random in 0..20 {
+            let sample = row.sample(random).unwrap();
+            let filled = sample.iter().filter(|&&filled| filled).count();
+
+            assert_eq!(sample.len(), 5);
+            assert!(!sample[2] && filled == 2);
+        }
+
+        assert_eq!(Row::new(&[F], &[2], 1).sample(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "clues must not be zero")]
+    fn test_zero_clue() {
+        row("?.?", &[1, 0], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "too many arrangements")]
+    fn test_overflow() {
+        Row::new(&[U; 200], &[1; 50], 1);
+    }
+}
@@ -0,0 +1,561 @@
+use std::{any, iter::Enumerate, marker::PhantomData, str::FromStr};
+
+use super::UCoords;
+use crate::{ParseError, ParseErrorKind, ParseResult};
+
+/// Returns an iterator over the lines of the input, as [`Span`]s that remember
+/// their position. Windows line endings and trailing blank lines are ignored.
+pub fn lines(input: &str) -> impl Iterator<Item = Span<'_>> + Clone {
+    input
+        .trim_end_matches(['\r', '\n'])
+        .lines()
+        .enumerate()
+        .map(|(i, line)| Span::new(i + 1, line))
+}
+
+/// Collects the lines of a rectangular grid, verifying that there is at least
+/// one line and that all of them have the same width.
+pub fn grid_lines(input: &str) -> ParseResult<Vec<Span<'_>>> {
+    let grid: Vec<_> = lines(input).collect();
+    check_grid(&grid)?;
+    Ok(grid)
+}
+
+/// Verifies that the lines form a non-empty rectangular grid.
+pub fn check_grid(grid: &[Span]) -> ParseResult<()> {
+    let width = grid.first().ok_or(ParseError::unexpected_end(0))?.len();
+
+    match grid.iter().find(|line| line.len() != width) {
+        Some(line) => {
+            let kind = ParseErrorKind::Invalid("line length");
+            Err(line.error_at(line.len().min(width), kind))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Parses a rectangular grid into a flat vector of cells in row-major order,
+/// returning it with the size of the grid. The `cell` function decodes a byte,
+/// with `what` describing the expected bytes for when it returns [`None`].
+pub fn grid<T>(
+    input: &str,
+    what: &'static str,
+    cell: impl Fn(u8) -> Option<T>,
+) -> ParseResult<(Vec<T>, UCoords)> {
+    grid_from_lines(&grid_lines(input)?, what, cell)
+}
+
+/// Same as [`grid()`], for lines that have already been split, such as those
+/// of a [`Paragraph`].
+pub fn grid_from_lines<T>(
+    lines: &[Span],
+    what: &'static str,
+    cell: impl Fn(u8) -> Option<T>,
+) -> ParseResult<(Vec<T>, UCoords)> {
+    check_grid(lines)?;
+
+    let size = UCoords::new(lines[0].len(), lines.len());
+    let mut cells = Vec::with_capacity(size.x * size.y);
+
+    for line in lines {
+        for (i, &byte) in line.as_bytes().iter().enumerate() {
+            let kind = ParseErrorKind::Expected(what);
+            cells.push(cell(byte).ok_or_else(|| line.error_at(i, kind))?);
+        }
+    }
+
+    Ok((cells, size))
+}
+
+/* == Paragraphs == */
+
+/// Returns an iterator over the paragraphs of the input, which are groups of
+/// lines separated by one or more blank lines.
+pub fn paragraphs(input: &str) -> Paragraphs<'_> {
+    Paragraphs {
+        input,
+        lines: input.lines().enumerate(),
+    }
+}
+
+/// Iterator returned by [`paragraphs()`].
+#[derive(Clone)]
+pub struct Paragraphs<'a> {
+    input: &'a str,
+    lines: Enumerate<std::str::Lines<'a>>,
+}
+
+/// A group of consecutive non-blank lines.
+#[derive(Clone, Copy, Debug)]
+pub struct Paragraph<'a> {
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Iterator for Paragraphs<'a> {
+    type Item = Paragraph<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_blank = |line: &str| line.trim().is_empty();
+
+        let (i, first) = self.lines.find(|(_, line)| !is_blank(line))?;
+        let mut last = first;
+
+        for (_, line) in self.lines.by_ref() {
+            if is_blank(line) {
+                break;
+            }
+
+            last = line;
+        }
+
+        let start = first.as_ptr() as usize - self.input.as_ptr() as usize;
+        let end = last.as_ptr() as usize - self.input.as_ptr() as usize + last.len();
+
+        Some(Paragraph {
+            number: i + 1,
+            text: &self.input[start..end],
+        })
+    }
+}
+
+impl<'a> Paragraph<'a> {
+    pub fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// Returns the 1-based line number of the first line.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = Span<'a>> + Clone {
+        let number = self.number;
+
+        self.text
+            .lines()
+            .enumerate()
+            .map(move |(i, line)| Span::new(number + i, line))
+    }
+
+    /// Returns the first line, which often acts as a header.
+    pub fn first_line(&self) -> Span<'a> {
+        self.lines().next().unwrap_or(Span::new(self.number, ""))
+    }
+}
+
+/* == Numbers == */
+
+/// Returns a fast iterator over all integers found in the input, skipping any
+/// bytes in between. For signed types, a `-` directly before the digits makes
+/// the number negative. Nothing is validated and overflow wraps around, so
+/// this is meant for well-formed input where only the numbers matter.
+pub fn numbers<T: Integer>(input: &(impl AsRef<[u8]> + ?Sized)) -> Numbers<'_, T> {
+    Numbers {
+        bytes: input.as_ref(),
+        _type: PhantomData,
+    }
+}
+
+/// Iterator returned by [`numbers()`].
+#[derive(Clone)]
+pub struct Numbers<'a, T> {
+    bytes: &'a [u8],
+    _type: PhantomData<T>,
+}
+
+impl<T: Integer> Iterator for Numbers<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.bytes.iter().position(u8::is_ascii_digit)?;
+        let negative = T::SIGNED && start > 0 && self.bytes[start - 1] == b'-';
+
+        let mut value = T::ZERO;
+        let mut end = start;
+
+        while let Some(digit) = self.bytes.get(end).filter(|b| b.is_ascii_digit()) {
+            value = value.push_digit(digit - b'0');
+            end += 1;
+        }
+
+        self.bytes = &self.bytes[end..];
+        Some(if negative { value.negate() } else { value })
+    }
+}
+
+/// Primitive integers that can be read by [`numbers()`].
+pub trait Integer: Copy {
+    const SIGNED: bool;
+    const ZERO: Self;
+
+    /// Appends a decimal digit, wrapping on overflow.
+    fn push_digit(self, digit: u8) -> Self;
+
+    /// Negates the value, wrapping on overflow.
+    fn negate(self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($signed:literal, $($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                const SIGNED: bool = $signed;
+                const ZERO: Self = 0;
+
+                fn push_digit(self, digit: u8) -> Self {
+                    self.wrapping_mul(10).wrapping_add(digit as Self)
+                }
+
+                fn negate(self) -> Self {
+                    self.wrapping_neg()
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(false, u8, u16, u32, u64, u128, usize);
+impl_integer!(true, i8, i16, i32, i64, i128, isize);
+
+/* == Spans == */
+
+/// A slice of a single input line that knows where it comes from, so that
+/// errors raised while parsing it point at the offending line and column.
+#[derive(Clone, Copy, Debug)]
+pub struct Span<'a> {
+    line: &'a str,
+    number: usize,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Span<'a> {
+    /// Creates a span covering the whole line with the given 1-based number.
+    pub fn new(number: usize, line: &'a str) -> Span<'a> {
+        Span {
+            line,
+            number,
+            start: 0,
+            end: line.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        &self.line[self.start..self.end]
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.as_str().as_bytes()
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the 1-based line number of the span.
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Returns the 1-based column at which the span starts.
+    pub fn column(&self) -> usize {
+        self.start + 1
+    }
+
+    /// Creates an error located at the start of the span.
+    pub fn error(&self, kind: ParseErrorKind) -> ParseError {
+        self.error_at(0, kind)
+    }
+
+    /// Creates an error located at the given byte offset into the span.
+    pub fn error_at(&self, offset: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError::new(self.number, self.start + offset + 1, kind).with_context(self.line)
+    }
+
+    /// Shorthand for an [`ParseErrorKind::Expected`] error at the start of the span.
+    pub fn expected(&self, what: &'static str) -> ParseError {
+        self.error(ParseErrorKind::Expected(what))
+    }
+
+    /// Returns the sub-span between the given byte offsets.
+    pub fn slice(&self, start: usize, end: usize) -> Span<'a> {
+        debug_assert!(start <= end && end <= self.len());
+
+        Span {
+            start: self.start + start,
+            end: self.start + end,
+            ..*self
+        }
+    }
+
+    pub fn split_at(&self, mid: usize) -> (Span<'a>, Span<'a>) {
+        (self.slice(0, mid), self.slice(mid, self.len()))
+    }
+
+    /// Splits the span around the first occurrence of the delimiter, or returns
+    /// an error pointing at the end of the span if it's missing.
+    pub fn split_once(&self, delimiter: &'static str) -> ParseResult<(Span<'a>, Span<'a>)> {
+        match self.as_str().find(delimiter) {
+            Some(i) => Ok((
+                self.slice(0, i),
+                self.slice(i + delimiter.len(), self.len()),
+            )),
+            None => Err(self.error_at(self.len(), ParseErrorKind::Expected(delimiter))),
+        }
+    }
+
+    pub fn split(self, delimiter: &'static str) -> impl Iterator<Item = Span<'a>> + Clone {
+        self.as_str().split(delimiter).map(move |s| self.subspan(s))
+    }
+
+    pub fn split_whitespace(self) -> impl DoubleEndedIterator<Item = Span<'a>> + Clone {
+        self.as_str()
+            .split_whitespace()
+            .map(move |s| self.subspan(s))
+    }
+
+    pub fn trim(&self) -> Span<'a> {
+        self.subspan(self.as_str().trim())
+    }
+
+    /// Parses each whitespace-separated word of the span.
+    pub fn numbers<T: FromStr>(
+        self,
+    ) -> impl DoubleEndedIterator<Item = ParseResult<T>> + Clone + 'a {
+        self.split_whitespace().map(|word| word.parse())
+    }
+
+    /// Splits a `key<separator>values` line, such as `seeds: 79 14 55` or
+    /// `broadcaster -> a, b, c`, into the trimmed key and the values, which
+    /// may be separated by commas and/or whitespace.
+    pub fn key_values(
+        self,
+        separator: &'static str,
+    ) -> ParseResult<(Span<'a>, impl Iterator<Item = Span<'a>> + Clone)> {
+        let (key, values) = self.split_once(separator)?;
+
+        let values = values
+            .as_str()
+            .split([' ', ','])
+            .filter(|value| !value.is_empty())
+            .map(move |value| values.subspan(value));
+
+        Ok((key.trim(), values))
+    }
+
+    /// Matches the span against a fixed pattern of literal text and `{}`
+    /// placeholders, returning what each placeholder captured. A placeholder
+    /// extends up to the first occurrence of the literal that follows it.
+    ///
+    /// ```text
+    /// let [x, y, z] = line.scan("{},{},{}")?;
+    /// ```
+    ///
+    /// # Panics
+    /// If the pattern does not have exactly `N` placeholders, or if two of
+    /// them are adjacent.
+    pub fn scan<const N: usize>(&self, pattern: &'static str) -> ParseResult<[Span<'a>; N]> {
+        assert_eq!(pattern.matches("{}").count(), N, "wrong number of captures");
+        assert!(
+            !pattern.contains("{}{}"),
+            "adjacent placeholders in pattern"
+        );
+
+        let mut captures = [*self; N];
+        let mut literals = pattern.split("{}");
+        let mut rest = self.strip_prefix(literals.next().unwrap_or_default())?;
+
+        for (capture, literal) in captures.iter_mut().zip(literals) {
+            // Only the last placeholder can be followed by an empty literal
+            if literal.is_empty() {
+                (*capture, rest) = rest.split_at(rest.len());
+            } else {
+                (*capture, rest) = rest.split_once(literal)?;
+            }
+        }
+
+        match rest.is_empty() {
+            true => Ok(captures),
+            false => Err(rest.expected("end of line")),
+        }
+    }
+
+    /// Removes the prefix, or returns an error if the span does not start with it.
+    pub fn strip_prefix(&self, prefix: &'static str) -> ParseResult<Span<'a>> {
+        match self.as_str().starts_with(prefix) {
+            true => Ok(self.slice(prefix.len(), self.len())),
+            false => Err(self.expected(prefix)),
+        }
+    }
+
+    /// Removes the suffix, or returns an error if the span does not end with it.
+    pub fn strip_suffix(&self, suffix: &'static str) -> ParseResult<Span<'a>> {
+        match self.as_str().ends_with(suffix) {
+            true => Ok(self.slice(0, self.len() - suffix.len())),
+            false => Err(self.error_at(self.len(), ParseErrorKind::Expected(suffix))),
+        }
+    }
+
+    /// Returns the byte at the given offset, or an error if the span is too short.
+    pub fn byte(&self, offset: usize) -> ParseResult<u8> {
+        self.as_bytes()
+            .get(offset)
+            .copied()
+            .ok_or_else(|| self.error_at(self.len(), ParseErrorKind::Expected("a character")))
+    }
+
+    /// Verifies that every byte of the span satisfies the predicate, or returns an
+    /// error pointing at the first one that doesn't.
+    pub fn check_bytes(&self, valid: impl Fn(u8) -> bool, what: &'static str) -> ParseResult<()> {
+        match self.as_bytes().iter().position(|&b| !valid(b)) {
+            Some(i) => Err(self.error_at(i, ParseErrorKind::Expected(what))),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the whole span using [`FromStr`], reporting the type name on failure.
+    pub fn parse<T: FromStr>(&self) -> ParseResult<T> {
+        self.as_str()
+            .parse()
+            .map_err(|_| self.error(ParseErrorKind::Invalid(short_type_name::<T>())))
+    }
+
+    /// Converts a subslice of this span's text (e.g. obtained from [`str`] methods)
+    /// back into a span.
+    pub fn subspan(&self, s: &'a str) -> Span<'a> {
+        let start = s.as_ptr() as usize - self.line.as_ptr() as usize;
+        debug_assert!(start >= self.start && start + s.len() <= self.end);
+
+        Span {
+            start,
+            end: start + s.len(),
+            ..*self
+        }
+    }
+}
+
+fn short_type_name<T>() -> &'static str {
+    let name = any::type_name::<T>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lines() {
+        let spans: Vec<_> = lines("a\r\nb c\r\n\r\n\n").collect();
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[1].as_str(), "b c");
+        assert_eq!(spans[1].number(), 2);
+    }
+
+    #[test]
+    fn test_grid_lines() {
+        assert_eq!(grid_lines("ab\ncd\n").map(|g| g.len()), Ok(2));
+        assert_eq!(grid_lines("ab\nc").unwrap_err().column(), 2);
+        assert!(grid_lines("").is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        let (cells, size) = grid("12\n34", "a digit", |c| Some(c - b'0')).unwrap();
+        assert_eq!(cells, [1, 2, 3, 4]);
+        assert_eq!(size, UCoords::new(2, 2));
+
+        let err = grid("12\n3x", "a digit", |c| c.is_ascii_digit().then_some(c)).unwrap_err();
+        assert_eq!((err.line(), err.column()), (2, 2));
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let input = "a\nb\n\n\nc\r\n\r\nd\ne\n";
+        let paragraphs: Vec<_> = paragraphs(input).collect();
+
+        assert_eq!(paragraphs.len(), 3);
+        assert_eq!(paragraphs[0].as_str(), "a\nb");
+        assert_eq!(paragraphs[1].as_str(), "c");
+        assert_eq!(paragraphs[2].number(), 7);
+        assert_eq!(paragraphs[2].first_line().as_str(), "d");
+        assert_eq!(paragraphs[2].lines().last().unwrap().number(), 8);
+    }
+
+    #[test]
+    fn test_numbers() {
+        let input = "x=-12, y=7 @ 3-4";
+
+        assert_eq!(numbers::<i32>(input).collect::<Vec<_>>(), [-12, 7, 3, -4]);
+        assert_eq!(numbers::<u32>(input).collect::<Vec<_>>(), [12, 7, 3, 4]);
+        assert_eq!(
+            numbers::<u8>(b"255 256".as_slice()).collect::<Vec<_>>(),
+            [255, 0]
+        );
+        assert_eq!(numbers::<u64>("").next(), None);
+    }
+
+    #[test]
+    fn test_span_numbers() {
+        let line = Span::new(1, "1 2  x");
+        let result: ParseResult<Vec<u8>> = line.numbers().collect();
+        assert_eq!(result.unwrap_err().column(), 6);
+    }
+
+    #[test]
+    fn test_key_values() {
+        let line = Span::new(1, "broadcaster -> a, b,c");
+        let (key, values) = line.key_values("->").unwrap();
+
+        assert_eq!(key.as_str(), "broadcaster");
+        assert_eq!(
+            values.map(|v| v.as_str()).collect::<Vec<_>>(),
+            ["a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_scan() {
+        let line = Span::new(1, "19, 13, 30 @ -2,  1, -2");
+        let [px, _, _, vx, _, vz] = line.scan("{}, {}, {} @ {}, {}, {}").unwrap();
+
+        assert_eq!(px.parse::<i64>(), Ok(19));
+        assert_eq!(vx.parse::<i64>(), Ok(-2));
+        assert_eq!(vz.column(), 22);
+
+        let err = line.scan::<2>("{} @ {}!").unwrap_err();
+        assert_eq!(err.column(), 24);
+
+        let err = line.scan::<1>("x{}").unwrap_err();
+        assert_eq!(err.column(), 1);
+    }
+
+    #[test]
+    fn test_span_positions() {
+        let line = Span::new(3, "Game 12: 3 blue");
+        let (id, sets) = line.split_once(": ").unwrap();
+        let id = id.strip_prefix("Game ").unwrap();
+
+        assert_eq!(id.parse::<u32>(), Ok(12));
+        assert_eq!(sets.column(), 10);
+        assert_eq!(sets.split_whitespace().nth(1).unwrap().column(), 12);
+    }
+
+    #[test]
+    fn test_error_display() {
+        let line = Span::new(2, "Game x: 3 blue");
+        let err = line.slice(5, 6).parse::<u32>().unwrap_err();
+
+        assert_eq!(err.line(), 2);
+        assert_eq!(err.column(), 6);
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 6: invalid u32\n  Game x: 3 blue\n       ^"
+        );
+    }
+}
@@ -0,0 +1,342 @@
+//! Exact fractions, for computations such as line intersections that must not
+//! depend on floating-point rounding.
+
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// A fraction `numerator / denominator` over [`i128`], always kept reduced with
+/// a positive denominator, so that equal values have equal representations.
+/// The operators panic on overflow, even in release builds, while the
+/// `checked_*` methods return [`None`] instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rational {
+    numerator: i128,
+    denominator: i128,
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational::integer(0);
+    pub const ONE: Rational = Rational::integer(1);
+
+    /// Creates the reduced fraction.
+    ///
+    /// # Panics
+    /// If the denominator is zero.
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert!(denominator != 0, "denominator must not be zero");
+
+        let divisor = gcd(numerator, denominator) * denominator.signum();
+
+        Self {
+            numerator: numerator / divisor,
+            denominator: denominator / divisor,
+        }
+    }
+
+    pub const fn integer(n: i128) -> Self {
+        Self {
+            numerator: n,
+            denominator: 1,
+        }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == 1
+    }
+
+    /// Returns the value if it is an integer.
+    pub fn to_integer(&self) -> Option<i128> {
+        self.is_integer().then_some(self.numerator)
+    }
+
+    /// Returns the largest integer less than or equal to the value.
+    pub fn floor(&self) -> i128 {
+        self.numerator.div_euclid(self.denominator)
+    }
+
+    /// Returns the smallest integer greater than or equal to the value.
+    pub fn ceil(&self) -> i128 {
+        -(-self.numerator).div_euclid(self.denominator)
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.numerator.signum()
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator,
+        }
+    }
+
+    /// Returns `1 / self`, or [`None`] for zero.
+    pub fn recip(&self) -> Option<Self> {
+        (self.numerator != 0).then(|| Self::new(self.denominator, self.numerator))
+    }
+
+    /// Returns `self + rhs`, or [`None`] on overflow.
+    pub fn checked_add(self, rhs: Rational) -> Option<Self> {
+        // Dividing by the common factor first keeps the intermediate values small
+        let divisor = gcd(self.denominator, rhs.denominator);
+        let left = rhs.denominator / divisor;
+        let right = self.denominator / divisor;
+
+        let numerator = self
+            .numerator
+            .checked_mul(left)?
+            .checked_add(rhs.numerator.checked_mul(right)?)?;
+
+        Some(Rational::new(
+            numerator,
+            self.denominator.checked_mul(left)?,
+        ))
+    }
+
+    /// Returns `self - rhs`, or [`None`] on overflow.
+    pub fn checked_sub(self, rhs: Rational) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    /// Returns `self * rhs`, or [`None`] on overflow.
+    pub fn checked_mul(self, rhs: Rational) -> Option<Self> {
+        // Cross-reducing first keeps the intermediate values small
+        let a = gcd(self.numerator, rhs.denominator);
+        let b = gcd(rhs.numerator, self.denominator);
+
+        Some(Rational::new(
+            (self.numerator / a).checked_mul(rhs.numerator / b)?,
+            (self.denominator / b).checked_mul(rhs.denominator / a)?,
+        ))
+    }
+
+    /// Returns `self / rhs`, or [`None`] on overflow or if `rhs` is zero.
+    pub fn checked_div(self, rhs: Rational) -> Option<Self> {
+        self.checked_mul(rhs.recip()?)
+    }
+
+    /// Returns `-self`, or [`None`] on overflow.
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Rational {
+            numerator: self.numerator.checked_neg()?,
+            denominator: self.denominator,
+        })
+    }
+}
+
+/// Returns the greatest common divisor of the absolute values, which is only 0
+/// if both are 0.
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+
+    // Only i128::MIN can't be represented, in which case nothing can be reduced
+    i128::try_from(a).unwrap_or(1).max(1)
+}
+
+/// Compares `n1 / d1` and `n2 / d2`, both in `[0, 1)`, without overflowing,
+/// by comparing the continued fractions of their reciprocals.
+fn cmp_fractions(mut n1: i128, mut d1: i128, mut n2: i128, mut d2: i128) -> Ordering {
+    loop {
+        match (n1, n2) {
+            (0, 0) => return Ordering::Equal,
+            (0, _) => return Ordering::Less,
+            (_, 0) => return Ordering::Greater,
+            _ => {}
+        }
+
+        // The larger reciprocal is the smaller fraction
+        let (q1, q2) = (d1 / n1, d2 / n2);
+
+        if q1 != q2 {
+            return q2.cmp(&q1);
+        }
+
+        // The reciprocals have the same integer part, so the order of their
+        // fractional parts is swapped
+        (n1, d1, n2, d2) = (d2 % n2, n2, d1 % n1, n1);
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Self::Output {
+        self.checked_add(rhs).expect("rational addition overflowed")
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("rational subtraction overflowed")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Self::Output {
+        self.checked_mul(rhs)
+            .expect("rational multiplication overflowed")
+    }
+}
+
+/// # Panics
+/// If `rhs` is zero.
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Rational) -> Self::Output {
+        let inverse = rhs.recip().expect("division by zero");
+        self.checked_mul(inverse)
+            .expect("rational division overflowed")
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().expect("rational negation overflowed")
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Compares the integer parts first, so that the fractional parts are
+        // below 1 and their cross products below the product of denominators
+        let (a, b) = (self.floor(), other.floor());
+
+        if a != b {
+            return a.cmp(&b);
+        }
+
+        let (n1, d1) = (self.numerator - a * self.denominator, self.denominator);
+        let (n2, d2) = (other.numerator - b * other.denominator, other.denominator);
+
+        match (n1.checked_mul(d2), n2.checked_mul(d1)) {
+            (Some(left), Some(right)) => left.cmp(&right),
+            _ => cmp_fractions(n1, d1, n2, d2),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Rational::integer(n as i128)
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Rational::integer(n)
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.denominator {
+            1 => write!(f, "{}", self.numerator),
+            _ => write!(f, "{}/{}", self.numerator, self.denominator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalisation() {
+        assert_eq!(Rational::new(6, -4), Rational::new(-3, 2));
+        assert_eq!(Rational::new(0, -5), Rational::ZERO);
+        assert_eq!(Rational::new(-3, 2).to_string(), "-3/2");
+        assert_eq!(Rational::new(8, 4).to_integer(), Some(2));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Rational::new(1, 6);
+        let b = Rational::new(3, 4);
+
+        assert_eq!(a + b, Rational::new(11, 12));
+        assert_eq!(a - b, Rational::new(-7, 12));
+        assert_eq!(a * b, Rational::new(1, 8));
+        assert_eq!(a / b, Rational::new(2, 9));
+        assert_eq!(Rational::ZERO.recip(), None);
+
+        // Would overflow without reducing first
+        let large = Rational::new(1, 1 << 100);
+        assert_eq!(large * Rational::integer(1 << 101), Rational::integer(2));
+        assert_eq!(large + large, Rational::new(1, 1 << 99));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max = Rational::integer(i128::MAX);
+
+        assert_eq!(max.checked_add(Rational::ONE), None);
+        assert_eq!(max.checked_mul(Rational::integer(2)), None);
+        assert_eq!(Rational::integer(i128::MIN).checked_neg(), None);
+        assert_eq!(Rational::ONE.checked_div(Rational::ZERO), None);
+        assert_eq!(max.checked_sub(max), Some(Rational::ZERO));
+    }
+
+    #[test]
+    #[should_panic(expected = "rational multiplication overflowed")]
+    fn test_overflow_panics() {
+        let _ = Rational::integer(i128::MAX) * Rational::integer(2);
+    }
+
+    #[test]
+    fn test_rounding_and_ordering() {
+        let x = Rational::new(-7, 2);
+
+        assert_eq!((x.floor(), x.ceil()), (-4, -3));
+        assert_eq!(Rational::integer(5).ceil(), 5);
+        assert!(x < Rational::new(-10, 3));
+        assert!(Rational::new(i128::MAX, 3) > Rational::new(i128::MAX - 1, 3));
+
+        // The cross products of the fractional parts overflow
+        let (p, q) = (i128::MAX, i128::MAX - 1);
+        assert!(Rational::new(q - 1, q) < Rational::new(p - 1, p));
+        assert!(Rational::new(1, q) > Rational::new(1, p));
+        assert_eq!(
+            Rational::new(5, 7).cmp(&Rational::new(10, 14)),
+            Ordering::Equal
+        );
+
+        let fractions = (1..12).flat_map(|d| (0..d).map(move |n| (n, d)));
+        let pairs = fractions
+            .clone()
+            .flat_map(|a| fractions.clone().map(move |b| (a, b)));
+
+        for ((n1, d1), (n2, d2)) in pairs {
+            assert_eq!(cmp_fractions(n1, d1, n2, d2), (n1 * d2).cmp(&(n2 * d1)));
+        }
+        assert_eq!(x.abs().signum(), 1);
+    }
+}
@@ -0,0 +1,605 @@
+//! Graph searches over user-defined states, where the graph is only described
+//! by a closure returning the neighbours of a state.
+//!
+//! The best known cost of each state is kept in a [`StateMap`], which is a
+//! [`HashMap`] by default. When states can be numbered, a [`DenseMap`] backed
+//! by a vector is much faster. Parents are only remembered when paths are
+//! tracked, to keep the map small.
+//!
+//! When costs are small integers, [`Search::astar_buckets`] keeps the pending
+//! states in a [`BucketQueue`] instead of a binary heap.
+
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, VecDeque},
+    hash::Hash,
+    marker::PhantomData,
+    ops::Add,
+};
+
+/// A cost that can be accumulated along a path, starting from [`Default`].
+pub trait Cost: Copy + Ord + Add<Output = Self> + Default {}
+
+impl<T: Copy + Ord + Add<Output = T> + Default> Cost for T {}
+
+/// A map from states to values, used to remember the visited states.
+pub trait StateMap<S, V> {
+    fn get(&self, state: &S) -> Option<&V>;
+    fn insert(&mut self, state: S, value: V);
+    fn clear(&mut self);
+}
+
+/// A [`StateMap`] backed by a vector, for states that can be numbered from 0
+/// to `len - 1` by the `index` function.
+pub struct DenseMap<V, F> {
+    values: Vec<Option<V>>,
+    index: F,
+}
+
+/// The result of a successful search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Found<S, C> {
+    /// The goal state that was reached.
+    pub state: S,
+    /// The cost of the cheapest path to the goal.
+    pub cost: C,
+    /// The states along that path, from a start to the goal, if paths are tracked.
+    pub path: Option<Vec<S>>,
+}
+
+/// A reusable search, that remembers the costs of the states that were visited
+/// by the last call until the next one.
+///
+/// ```
+/// use advent_of_code::tools::search::Search;
+///
+/// // Reach 10 from 1, by either adding 1 or doubling
+/// let found = Search::new()
+///     .track_paths()
+///     .bfs([1], |&n| [n + 1, n * 2], |&n| n == 10)
+///     .unwrap();
+///
+/// assert_eq!(found.cost, 4);
+/// assert_eq!(found.path, Some(vec![1, 2, 4, 5, 10]));
+/// ```
+pub struct Search<S, C, M = HashMap<S, C>> {
+    visited: M,
+    parents: Option<HashMap<S, S>>,
+    marker: PhantomData<C>,
+}
+
+/// A monotone priority queue for integer priorities, with a bucket of items
+/// for each priority from the last one popped. Pushing and popping are O(1)
+/// amortised, as long as the pending priorities span a small range, such as
+/// the costs of Dijkstra's algorithm when each step costs a few units.
+///
+/// ```
+/// use advent_of_code::tools::search::BucketQueue;
+///
+/// let mut queue = BucketQueue::new();
+/// queue.push(3, 'c');
+/// queue.push(1, 'a');
+///
+/// assert_eq!(queue.pop(), Some((1, 'a')));
+/// queue.push(2, 'b');
+/// assert_eq!(queue.pop(), Some((2, 'b')));
+/// assert_eq!(queue.pop(), Some((3, 'c')));
+/// assert_eq!(queue.pop(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BucketQueue<T> {
+    /// The items of each priority, starting from `base`.
+    buckets: VecDeque<Vec<T>>,
+    base: usize,
+    len: usize,
+}
+
+/// A state in the priority queue, ordered by lowest priority first.
+struct Entry<S, C> {
+    priority: C,
+    cost: C,
+    state: S,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<S: Hash + Eq, V> StateMap<S, V> for HashMap<S, V> {
+    fn get(&self, state: &S) -> Option<&V> {
+        HashMap::get(self, state)
+    }
+
+    fn insert(&mut self, state: S, value: V) {
+        HashMap::insert(self, state, value);
+    }
+
+    fn clear(&mut self) {
+        HashMap::clear(self);
+    }
+}
+
+impl<V, F> DenseMap<V, F> {
+    pub fn new<S>(len: usize, index: F) -> Self
+    where
+        F: Fn(&S) -> usize,
+    {
+        Self {
+            values: (0..len).map(|_| None).collect(),
+            index,
+        }
+    }
+}
+
+impl<S, V, F: Fn(&S) -> usize> StateMap<S, V> for DenseMap<V, F> {
+    fn get(&self, state: &S) -> Option<&V> {
+        self.values[(self.index)(state)].as_ref()
+    }
+
+    fn insert(&mut self, state: S, value: V) {
+        self.values[(self.index)(&state)] = Some(value);
+    }
+
+    fn clear(&mut self) {
+        self.values.iter_mut().for_each(|value| *value = None);
+    }
+}
+
+impl<S: Hash + Eq, C> Search<S, C> {
+    /// Creates a search that remembers visited states in a [`HashMap`].
+    pub fn new() -> Self {
+        Self::with_map(HashMap::new())
+    }
+}
+
+impl<S: Hash + Eq, C> Default for Search<S, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, C, M> Search<S, C, M> {
+    /// Creates a search that remembers visited states in the given map.
+    pub fn with_map(visited: M) -> Self {
+        Self {
+            visited,
+            parents: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Remembers the parent of each state, so that paths can be reconstructed.
+    pub fn track_paths(mut self) -> Self {
+        self.parents = Some(HashMap::new());
+        self
+    }
+}
+
+impl<S: Clone + Hash + Eq, C: Cost, M: StateMap<S, C>> Search<S, C, M> {
+    /// Returns the cost of the cheapest path to a state found by the last search.
+    /// For states that were not settled before the goal was reached, this may
+    /// be larger than the actual cheapest cost.
+    pub fn cost(&self, state: &S) -> Option<C> {
+        self.visited.get(state).copied()
+    }
+
+    /// Returns the path to a state found by the last search, if paths are tracked.
+    pub fn path(&self, to: &S) -> Option<Vec<S>> {
+        let parents = self.parents.as_ref()?;
+        self.visited.get(to)?;
+
+        let mut path = vec![to.clone()];
+
+        while let Some(parent) = parents.get(path.last()?) {
+            path.push(parent.clone());
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    /// Finds the cheapest path from any of the starts to a state that satisfies
+    /// `goal`, where `neighbours` returns the next states with the cost of the
+    /// step to reach them.
+    pub fn dijkstra<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        neighbours: impl FnMut(&S) -> I,
+        goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, C>>
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        self.astar(starts, neighbours, |_| C::default(), goal)
+    }
+
+    /// Same as [`Search::dijkstra`], exploring states in order of their cost
+    /// plus `heuristic`. The heuristic must never overestimate the remaining
+    /// cost to the goal, or a more expensive path may be returned.
+    pub fn astar<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> C,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, C>>
+    where
+        I: IntoIterator<Item = (S, C)>,
+    {
+        let mut queue = BinaryHeap::new();
+        self.clear();
+
+        for state in starts {
+            let cost = C::default();
+            queue.push(Entry::new(heuristic(&state), cost, state.clone()));
+            self.visited.insert(state, cost);
+        }
+
+        while let Some(Entry { cost, state, .. }) = queue.pop() {
+            // Skip entries that were superseded by a cheaper path
+            if self.cost(&state).is_some_and(|best| best < cost) {
+                continue;
+            }
+
+            if goal(&state) {
+                return Some(self.found(state, cost));
+            }
+
+            for (next, step) in neighbours(&state) {
+                let next_cost = cost + step;
+
+                if self.cost(&next).is_some_and(|best| best <= next_cost) {
+                    continue;
+                }
+
+                let priority = next_cost + heuristic(&next);
+                self.visit(&state, next.clone(), next_cost);
+                queue.push(Entry::new(priority, next_cost, next));
+            }
+        }
+
+        None
+    }
+
+    fn clear(&mut self) {
+        self.visited.clear();
+
+        if let Some(parents) = &mut self.parents {
+            parents.clear();
+        }
+    }
+
+    fn visit(&mut self, parent: &S, state: S, cost: C) {
+        if let Some(parents) = &mut self.parents {
+            parents.insert(state.clone(), parent.clone());
+        }
+
+        self.visited.insert(state, cost);
+    }
+
+    fn found(&self, state: S, cost: C) -> Found<S, C> {
+        Found {
+            path: self.path(&state),
+            state,
+            cost,
+        }
+    }
+}
+
+impl<S: Clone + Hash + Eq, M: StateMap<S, usize>> Search<S, usize, M> {
+    /// Finds the shortest path from any of the starts to a state that satisfies
+    /// `goal`, where each step has a cost of 1. With a goal that is never
+    /// satisfied, this computes the distance to every reachable state.
+    pub fn bfs<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, usize>>
+    where
+        I: IntoIterator<Item = S>,
+    {
+        let mut queue = VecDeque::new();
+        self.clear();
+
+        for state in starts {
+            queue.push_back((state.clone(), 0));
+            self.visited.insert(state, 0);
+        }
+
+        while let Some((state, cost)) = queue.pop_front() {
+            if goal(&state) {
+                return Some(self.found(state, cost));
+            }
+
+            for next in neighbours(&state) {
+                if self.visited.get(&next).is_some() {
+                    continue;
+                }
+
+                self.visit(&state, next.clone(), cost + 1);
+                queue.push_back((next, cost + 1));
+            }
+        }
+
+        None
+    }
+
+    /// Same as [`Search::astar`], with the pending states in a [`BucketQueue`]
+    /// instead of a binary heap, which is faster when the steps are cheap.
+    /// The heuristic must also be consistent, never decreasing by more than
+    /// the cost of a step, so that priorities are popped in order. A zero
+    /// heuristic turns this into Dijkstra's algorithm.
+    pub fn astar_buckets<I>(
+        &mut self,
+        starts: impl IntoIterator<Item = S>,
+        mut neighbours: impl FnMut(&S) -> I,
+        mut heuristic: impl FnMut(&S) -> usize,
+        mut goal: impl FnMut(&S) -> bool,
+    ) -> Option<Found<S, usize>>
+    where
+        I: IntoIterator<Item = (S, usize)>,
+    {
+        let mut queue = BucketQueue::new();
+        self.clear();
+
+        for state in starts {
+            queue.push(heuristic(&state), (0, state.clone()));
+            self.visited.insert(state, 0);
+        }
+
+        while let Some((_, (cost, state))) = queue.pop() {
+            // Skip entries that were superseded by a cheaper path
+            if self.cost(&state).is_some_and(|best| best < cost) {
+                continue;
+            }
+
+            if goal(&state) {
+                return Some(self.found(state, cost));
+            }
+
+            for (next, step) in neighbours(&state) {
+                let next_cost = cost + step;
+
+                if self.cost(&next).is_some_and(|best| best <= next_cost) {
+                    continue;
+                }
+
+                let priority = next_cost + heuristic(&next);
+                self.visit(&state, next.clone(), next_cost);
+                queue.push(priority, (next_cost, next));
+            }
+        }
+
+        None
+    }
+}
+
+impl<T> BucketQueue<T> {
+    pub fn new() -> Self {
+        Self {
+            buckets: VecDeque::new(),
+            base: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds an item with the given priority.
+    ///
+    /// # Panics
+    /// If the priority is lower than the one of the last item popped.
+    pub fn push(&mut self, priority: usize, item: T) {
+        assert!(priority >= self.base, "priorities must not decrease");
+        let offset = priority - self.base;
+
+        if offset >= self.buckets.len() {
+            self.buckets.resize_with(offset + 1, Vec::new);
+        }
+
+        self.buckets[offset].push(item);
+        self.len += 1;
+    }
+
+    /// Removes an item with the lowest priority, returning it with its
+    /// priority. Items of the same priority are popped last in, first out.
+    pub fn pop(&mut self) -> Option<(usize, T)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        loop {
+            if let Some(item) = self.buckets.front_mut()?.pop() {
+                self.len -= 1;
+                return Some((self.base, item));
+            }
+
+            // Reuse the empty bucket for the next priority after the last one
+            self.buckets.rotate_left(1);
+            self.base += 1;
+        }
+    }
+}
+
+impl<T> Default for BucketQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, C> Entry<S, C> {
+    fn new(priority: C, cost: C, state: S) -> Self {
+        Self {
+            priority,
+            cost,
+            state,
+        }
+    }
+}
+
+impl<S, C: Ord> PartialEq for Entry<S, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S, C: Ord> Eq for Entry<S, C> {}
+
+impl<S, C: Ord> PartialOrd for Entry<S, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S, C: Ord> Ord for Entry<S, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::{Grid, UCoords};
+
+    const MAZE: &str = "\
+        .....\n\
+        .###.\n\
+        ...#.\n\
+        .#.#.\n\
+        .#...";
+
+    fn maze() -> Grid<u8> {
+        Grid::parse(MAZE).unwrap()
+    }
+
+    fn open_neighbours(grid: &Grid<u8>, at: UCoords) -> impl Iterator<Item = UCoords> + '_ {
+        grid.neighbours(at).filter(|&next| grid[next] == b'.')
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = maze();
+        let end = UCoords::new(4, 4);
+
+        let mut search = Search::new().track_paths();
+        let found = search.bfs(
+            [UCoords::new(0, 0)],
+            |&at| open_neighbours(&grid, at),
+            |&at| at == end,
+        );
+        let found = found.unwrap();
+
+        assert_eq!(found.cost, 8);
+        assert_eq!(found.path.unwrap().len(), 9);
+
+        // Exhaustive search
+        assert!(search
+            .bfs([end], |&at| open_neighbours(&grid, at), |_| false)
+            .is_none());
+        assert_eq!(search.cost(&UCoords::new(0, 4)), Some(8));
+        assert_eq!(search.cost(&UCoords::new(1, 1)), None);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        // Going through 1 is cheaper than going directly to 3
+        let edges = [vec![(1, 2), (3, 10)], vec![(2, 3)], vec![(3, 1)], vec![]];
+
+        let found = Search::new()
+            .track_paths()
+            .dijkstra([0], |&n| edges[n].clone(), |&n| n == 3)
+            .unwrap();
+
+        assert_eq!(found.cost, 6);
+        assert_eq!(found.path, Some(vec![0, 1, 2, 3]));
+
+        let unreachable = Search::new().dijkstra([3], |&n| edges[n].clone(), |&n| n == 0);
+        assert_eq!(unreachable, None);
+    }
+
+    #[test]
+    fn test_bucket_queue() {
+        let mut queue = BucketQueue::new();
+
+        for (priority, item) in [(5, 'e'), (0, 'a'), (5, 'f'), (2, 'b')] {
+            queue.push(priority, item);
+        }
+
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.pop(), Some((0, 'a')));
+        assert_eq!(queue.pop(), Some((2, 'b')));
+
+        // Pushing at the current priority, or far beyond the buckets
+        queue.push(2, 'c');
+        queue.push(100, 'z');
+        assert_eq!(queue.pop(), Some((2, 'c')));
+        assert_eq!(queue.pop(), Some((5, 'f')));
+        assert_eq!(queue.pop(), Some((5, 'e')));
+        assert_eq!(queue.pop(), Some((100, 'z')));
+        assert!(queue.is_empty());
+        assert_eq!(queue.pop(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bucket_queue_decrease() {
+        let mut queue = BucketQueue::new();
+        queue.push(3, ());
+        queue.pop();
+        queue.push(2, ());
+    }
+
+    #[test]
+    fn test_astar_dense() {
+        let grid = maze();
+        let end = UCoords::new(4, 4);
+        let width = grid.width();
+
+        let visited = DenseMap::new(width * grid.height(), |at: &UCoords| at.y * width + at.x);
+        let mut search = Search::with_map(visited);
+
+        let found = search.astar(
+            [UCoords::new(0, 0)],
+            |&at| open_neighbours(&grid, at).map(|next| (next, 1)),
+            |at| (end.x - at.x) + (end.y - at.y),
+            |&at| at == end,
+        );
+
+        assert_eq!(found.map(|f| f.cost), Some(8));
+        assert_eq!(search.path(&end), None);
+    }
+
+    #[test]
+    fn test_astar_buckets() {
+        let grid = maze();
+        let end = UCoords::new(4, 4);
+        let manhattan = |at: &UCoords| (end.x - at.x) + (end.y - at.y);
+
+        let mut search = Search::new().track_paths();
+        let found = search
+            .astar_buckets(
+                [UCoords::new(0, 0)],
+                |&at| open_neighbours(&grid, at).map(|next| (next, 1)),
+                manhattan,
+                |&at| at == end,
+            )
+            .unwrap();
+
+        assert_eq!(found.cost, 8);
+        assert_eq!(found.path.unwrap().len(), 9);
+
+        // Same as the binary heap, with weighted steps and no heuristic
+        let edges = [vec![(1, 2), (3, 10)], vec![(2, 3)], vec![(3, 1)], vec![]];
+        let mut search = Search::new().track_paths();
+        let found = search.astar_buckets([0], |&n| edges[n].clone(), |_| 0, |&n| n == 3);
+
+        assert_eq!(found.map(|f| f.cost), Some(6));
+        assert_eq!(search.path(&3), Some(vec![0, 1, 2, 3]));
+    }
+}
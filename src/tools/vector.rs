@@ -0,0 +1,188 @@
+//! Operations shared by 2D and 3D integer coordinates, so that neighbours and
+//! bounding boxes work the same in any dimension.
+
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::{Coords, Coords3};
+
+/// Integer coordinates with componentwise arithmetic.
+pub trait Vector:
+    'static
+    + Copy
+    + Eq
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + Mul<i64, Output = Self>
+{
+    /// The unit vectors pointing along each positive axis.
+    const AXES: &'static [Self];
+
+    fn dot(self, other: Self) -> i64;
+
+    /// Returns the smallest value of each component.
+    fn component_min(self, other: Self) -> Self;
+
+    /// Returns the largest value of each component.
+    fn component_max(self, other: Self) -> Self;
+
+    /// Returns the largest absolute value of the components, also known as
+    /// the Chebyshev distance to the origin.
+    fn norm_linf(self) -> u64;
+
+    /// Returns the sum of the absolute values of the components, also known
+    /// as the Manhattan distance to the origin.
+    fn norm_l1(self) -> u64;
+
+    /// Iterates over the orthogonal neighbours, two along each axis.
+    fn neighbours(self) -> impl Iterator<Item = Self> {
+        Self::AXES
+            .iter()
+            .flat_map(move |&axis| [self - axis, self + axis])
+    }
+}
+
+/// The smallest box containing a set of points, with inclusive bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BoundingBox<V> {
+    pub min: V,
+    pub max: V,
+}
+
+/* -------------------------------------------------------------------------- */
+
+impl<V: Vector> BoundingBox<V> {
+    /// Returns the bounding box of the points, or [`None`] if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = V>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::point(first), |bounds, point| bounds.including(point)))
+    }
+
+    /// Returns the box that only contains one point.
+    pub fn point(point: V) -> Self {
+        Self {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// Returns the smallest box that contains this box and the point.
+    pub fn including(self, point: V) -> Self {
+        Self {
+            min: self.min.component_min(point),
+            max: self.max.component_max(point),
+        }
+    }
+
+    pub fn contains(&self, point: V) -> bool {
+        self.min.component_max(point) == point && self.max.component_min(point) == point
+    }
+
+    /// Returns the overlap of the boxes, if they overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self.min.component_max(other.min);
+        let max = self.max.component_min(other.max);
+        (min.component_min(max) == min).then_some(Self { min, max })
+    }
+
+    /// Returns the number of lattice points along each axis.
+    pub fn size(&self) -> V {
+        let extent = self.max - self.min;
+        V::AXES.iter().fold(extent, |size, &axis| size + axis)
+    }
+}
+
+impl Vector for Coords {
+    const AXES: &'static [Self] = &[Coords { x: 1, y: 0 }, Coords { x: 0, y: 1 }];
+
+    fn dot(self, other: Self) -> i64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    fn component_min(self, other: Self) -> Self {
+        Coords::new(self.x.min(other.x), self.y.min(other.y))
+    }
+
+    fn component_max(self, other: Self) -> Self {
+        Coords::new(self.x.max(other.x), self.y.max(other.y))
+    }
+
+    fn norm_linf(self) -> u64 {
+        self.x.unsigned_abs().max(self.y.unsigned_abs())
+    }
+
+    fn norm_l1(self) -> u64 {
+        Coords::norm_l1(&self)
+    }
+}
+
+impl Vector for Coords3 {
+    const AXES: &'static [Self] = &[
+        Coords3 { x: 1, y: 0, z: 0 },
+        Coords3 { x: 0, y: 1, z: 0 },
+        Coords3 { x: 0, y: 0, z: 1 },
+    ];
+
+    fn dot(self, other: Self) -> i64 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    fn component_min(self, other: Self) -> Self {
+        Coords3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    fn component_max(self, other: Self) -> Self {
+        Coords3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
+    }
+
+    fn norm_linf(self) -> u64 {
+        [self.x, self.y, self.z]
+            .map(i64::unsigned_abs)
+            .into_iter()
+            .max()
+            .unwrap()
+    }
+
+    fn norm_l1(self) -> u64 {
+        Coords3::norm_l1(&self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::UCoords;
+
+    #[test]
+    fn test_coords() {
+        let at = Coords::new(3, -4);
+        let neighbours: Vec<_> = at.neighbours().collect();
+
+        assert_eq!(neighbours[0], Coords::new(2, -4));
+        assert_eq!(neighbours[3], Coords::new(3, -3));
+        assert_eq!(at.norm_linf(), 4);
+        assert_eq!(at.dot(Coords::new(4, 3)), 0);
+        assert_eq!(at.cross(Coords::new(4, 3)), 25);
+        assert_eq!(-at / 2, Coords::new(-1, 2));
+
+        let bounds = BoundingBox::from_points(neighbours).unwrap();
+        assert_eq!(bounds.size(), Coords::new(3, 3));
+        assert!(bounds.contains(at));
+
+        assert_eq!(
+            UCoords::try_from(at.component_max(Coords::default())).ok(),
+            Some(UCoords::new(3, 0))
+        );
+        assert!(UCoords::try_from(at).is_err());
+    }
+}